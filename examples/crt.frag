@@ -0,0 +1,14 @@
+#version 410
+out mediump vec4 FragColor;
+
+in mediump vec2 TexCoord;
+
+uniform sampler2D Texture;
+uniform float Lines;
+
+void main() {
+	vec4 color = texture(Texture, TexCoord);
+	float scanline = 0.75 + 0.25 * sin(TexCoord.y * Lines * 3.14159);
+
+	FragColor = vec4(color.rgb * scanline, color.a);
+}
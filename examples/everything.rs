@@ -21,19 +21,19 @@ fn main() {
 		smitty.sdf(SignedDistance::line_segment(
 			(-2, 2),
 			(2, -2),
-			2,
+			0.1,
 			Color::rgb(0.1, 0.3, 0.5),
 		));
 
 		smitty.sdf(SignedDistance::Circle {
 			center: Vec2::new(-1.0, 0.0),
-			radius: 5,
+			radius: 0.2,
 			color: Color::grey(0.8),
 		});
 
 		smitty.sdf(SignedDistance::Circle {
 			center: Vec2::new(1.0, 0.0),
-			radius: 5,
+			radius: 0.2,
 			color: Color::grey(0.8),
 		});
 
@@ -0,0 +1,48 @@
+use smitten::{self, Color, FillRule, Key, LineCap, LineJoin, Path, Smitten, StrokeStyle};
+
+fn main() {
+	let mut smitty = Smitten::new((720, 480), "Paths", 24);
+
+	let graph = Path::new()
+		.move_to((-12.0, -6.0))
+		.line_to((-9.0, -2.0))
+		.line_to((-6.0, -4.0))
+		.cubic_to((-4.0, 2.0), (-2.0, 2.0), (0.0, -1.0));
+
+	let badge = Path::new()
+		.move_to((4.0, 0.0))
+		.line_to((10.0, 0.0))
+		.arc_to((12.0, 0.0), (12.0, 2.0), 1.0)
+		.line_to((12.0, 6.0))
+		.quad_to((8.0, 9.0), (4.0, 6.0))
+		.close();
+
+	loop {
+		let _events = smitty.events();
+
+		// Quit on escape
+		if smitty.is_key_down(Key::Escape) {
+			break;
+		}
+
+		// Clear the screen
+		smitty.clear();
+
+		smitty.fill_path(&badge, FillRule::NonZero, Color::rgb(0.1, 0.3, 0.7));
+		smitty.stroke_path(
+			&badge,
+			StrokeStyle::new(0.2).join(LineJoin::Round),
+			Color::WHITE,
+		);
+		smitty.stroke_path(
+			&graph,
+			StrokeStyle::new(0.3)
+				.join(LineJoin::Miter)
+				.cap(LineCap::Round),
+			Color::rgb(0.9, 0.6, 0.1),
+		);
+
+		// Swap buffers
+		smitty.swap();
+	}
+}
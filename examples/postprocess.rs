@@ -0,0 +1,32 @@
+use smitten::{self, Color, Key, Smitten};
+
+fn main() {
+	let mut smitty = Smitten::new((720, 480), "Post Processing", 24);
+	let scene = smitty.create_render_target((720, 480));
+
+	// Edit crt.frag while this is running and it'll reload
+	let crt = smitty.load_fragment_shader("examples/crt.frag");
+	smitty.set_uniform(crt, "Lines", 240.0);
+
+	loop {
+		let _events = smitty.events();
+
+		// Quit on escape
+		if smitty.is_key_down(Key::Escape) {
+			break;
+		}
+
+		// Draw the scene offscreen...
+		smitty.with_render_target(scene, |smitty| {
+			smitty.clear();
+			smitty.rect((0, 0), (10, 10), Color::rgb(0.1, 0.3, 0.5));
+		});
+
+		// ...and then put it on the screen through our shader
+		smitty.clear();
+		smitty.post_process(scene, &[crt]);
+
+		// Swap buffers
+		smitty.swap();
+	}
+}
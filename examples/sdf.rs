@@ -1,4 +1,6 @@
-use smitten::{self, Color, SignedDistance, Smitten, Vec2, VirtualKeyCode};
+use smitten::{
+	self, Color, Gradient, SdfShape, SdfStyle, SignedDistance, Smitten, Vec2, VirtualKeyCode,
+};
 
 fn main() {
 	let mut smitty = Smitten::new((720, 480), "Square", 24);
@@ -16,17 +18,48 @@ fn main() {
 
 		smitty.sdf(SignedDistance::Circle {
 			center: Vec2::new(-2.0, -2.0),
-			radius: 48,
+			radius: 2.0,
 			color: Color::rgb(0.1, 0.3, 0.7),
 		});
 
 		smitty.sdf(SignedDistance::LineSegment {
 			color: Color::rgb(0.1, 0.3, 0.7),
 			start: Vec2::new(5.0, 5.0),
-			thickness: 5,
+			thickness: 0.2,
 			end: Vec2::new(0.0, 0.0),
 		});
 
+		// A blob with a hole in it, drawn in one pass
+		let blob = SdfShape::from(SignedDistance::circle((0.0, 0.0), 1.5, Color::WHITE))
+			.smooth_union(SignedDistance::circle((1.5, 0.0), 1.0, Color::WHITE), 0.5)
+			.subtract(SignedDistance::rounded_rect(
+				(0.0, 0.0),
+				(1.0, 1.0),
+				0.2,
+				Color::WHITE,
+			))
+			.rotate(0.4)
+			.translate((-6.0, 4.0));
+		smitty
+			.sdf_shape(&blob, Color::rgb(0.7, 0.3, 0.1), SdfStyle::default())
+			.unwrap();
+
+		// A sky behind a sun
+		smitty.rect(
+			(8.0, -5.0),
+			(8.0, 4.0),
+			Gradient::vertical(Color::rgb(0.9, 0.5, 0.2), Color::rgb(0.2, 0.3, 0.8)),
+		);
+		smitty.sdf_filled(
+			SignedDistance::circle((8.0, -5.0), 1.5, Color::WHITE),
+			Gradient::radial(
+				(0.5, 0.5),
+				0.5,
+				[(0.0, Color::YELLOW), (1.0, Color::rgb(1.0, 0.5, 0.0))],
+			),
+			SdfStyle::default(),
+		);
+
 		// Swap buffers
 		smitty.swap();
 	}
@@ -0,0 +1,38 @@
+// Gradients. Linear ones go from GradientPoints.xy to .zw and radial ones
+// out from .xy for .z. Corners takes the bottom left, bottom right, top left,
+// and top right colors from the first four stops.
+uniform int GradientKind;
+uniform vec4 GradientPoints;
+uniform vec4 GradientColors[8];
+uniform float GradientOffsets[8];
+uniform int GradientStops;
+
+vec4 gradient(vec2 p) {
+	if (GradientKind == 2) {
+		vec4 bottom = mix(GradientColors[0], GradientColors[1], p.x);
+		vec4 top = mix(GradientColors[2], GradientColors[3], p.x);
+		return mix(bottom, top, p.y);
+	}
+
+	float t;
+	if (GradientKind == 0) {
+		vec2 line = GradientPoints.zw - GradientPoints.xy;
+		t = dot(p - GradientPoints.xy, line) / max(dot(line, line), 0.00001);
+	} else {
+		t = length(p - GradientPoints.xy) / max(GradientPoints.z, 0.00001);
+	}
+
+	if (t <= GradientOffsets[0]) {
+		return GradientColors[0];
+	}
+
+	for (int i = 1; i < GradientStops; i++) {
+		if (t <= GradientOffsets[i]) {
+			float between = GradientOffsets[i] - GradientOffsets[i - 1];
+			float f = (t - GradientOffsets[i - 1]) / max(between, 0.00001);
+			return mix(GradientColors[i - 1], GradientColors[i], f);
+		}
+	}
+
+	return GradientColors[GradientStops - 1];
+}
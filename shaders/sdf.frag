@@ -8,49 +8,213 @@ flat in int vertexDrawMethod;
 
 out vec4 FragColor;
 
-void main() {
-	if (vertexDrawMethod == 1) {
-		vec2 center = gl_FragCoord.xy - vertexPointPair.xy;
-		float dist = length(center) - vertexPointPair.z;
-
-		if (dist <= 0.0) {
-			FragColor = vertexColor;
-		} else if (dist <= 2.0) {
-			FragColor = vec4(0.0, 0.0, 0.0, 1.0);
-		} else {
-			FragColor = vec4(1.0, 0.0, 0.0, 0.0);
-		}
-	} else if (vertexDrawMethod == 2) {
-		vec2 p1 = vertexPointPair.xy;
-		vec2 p2 = vertexPointPair.zw;
+// Set while drawing clipping masks so see-through pixels don't count
+uniform bool DiscardTransparent;
+
+// Everything is in pixels. A stroke width of zero means there's no stroke.
+uniform vec4 StrokeColor;
+uniform float StrokeWidth;
+uniform vec4 ShadowColor;
+uniform vec2 ShadowOffset;
+uniform float ShadowSoftness;
+
+// An SdfShape flattened into instructions run with a stack, five vec4s each.
+// The first is the op, the draw method, k for smooth unions, and how much to
+// scale the distance. Shapes have their PointPair and Parameters next and
+// then a matrix and offset to move the pixel to where the shape was defined.
+uniform vec4 Shape[24 * 5];
+uniform int ShapeLength;
+
+// Fill with the gradient instead of the color. GradientBounds is the bottom
+// left and size of the shape in pixels.
+uniform bool Gradient;
+uniform vec4 GradientBounds;
+
+#include "gradient.glsl"
+
+const float PI = 3.14159265;
 
-		vec2 point = gl_FragCoord.xy;
+vec2 rotate(vec2 p, float angle) {
+	float s = sin(angle);
+	float c = cos(angle);
+	return vec2(c * p.x - s * p.y, s * p.x + c * p.y);
+}
+
+float sdRoundedBox(vec2 p, vec2 b, float r) {
+	r = min(r, min(b.x, b.y));
+	vec2 q = abs(p) - b + r;
+	return length(max(q, 0.0)) + min(max(q.x, q.y), 0.0) - r;
+}
+
+// An arc on the circle of radius `ra` going `aperture` either side of +Y,
+// `rb` thick either side of the circle with round ends.
+float sdArc(vec2 p, float aperture, float ra, float rb) {
+	vec2 sc = vec2(sin(aperture), cos(aperture));
+	p.x = abs(p.x);
+	return ((sc.y * p.x > sc.x * p.y) ? length(p - sc * ra) : abs(length(p) - ra)) - rb;
+}
+
+float sdTriangle(vec2 p, vec2 p0, vec2 p1, vec2 p2) {
+	vec2 e0 = p1 - p0, e1 = p2 - p1, e2 = p0 - p2;
+	vec2 v0 = p - p0, v1 = p - p1, v2 = p - p2;
+	vec2 pq0 = v0 - e0 * clamp(dot(v0, e0) / dot(e0, e0), 0.0, 1.0);
+	vec2 pq1 = v1 - e1 * clamp(dot(v1, e1) / dot(e1, e1), 0.0, 1.0);
+	vec2 pq2 = v2 - e2 * clamp(dot(v2, e2) / dot(e2, e2), 0.0, 1.0);
+	float s = sign(e0.x * e2.y - e0.y * e2.x);
+	vec2 d = min(min(vec2(dot(pq0, pq0), s * (v0.x * e0.y - v0.y * e0.x)),
+	                 vec2(dot(pq1, pq1), s * (v1.x * e1.y - v1.y * e1.x))),
+	                 vec2(dot(pq2, pq2), s * (v2.x * e2.y - v2.y * e2.x)));
+	return -sqrt(d.x) * sign(d.y);
+}
+
+// Not exact away from the edge, but close enough to draw with
+float sdEllipse(vec2 p, vec2 r) {
+	float k0 = length(p / r);
+	float k1 = length(p / (r * r));
+	return k0 * (k0 - 1.0) / k1;
+}
+
+float sdPolygon(vec2 p, float r, float sides) {
+	float an = PI / sides;
+	vec2 acs = vec2(cos(an), sin(an));
+	float bn = mod(atan(p.x, p.y), 2.0 * an) - an;
+	p = length(p) * vec2(cos(bn), abs(sin(bn)));
+	p -= r * acs;
+	p.y += clamp(-p.y, 0.0, r * acs.y);
+	return length(p) * sign(p.x);
+}
+
+// Distance from `point` to the edge of the shape, negative inside of it.
+float shapeDistance(int method, vec4 pointPair, vec4 parameters, vec2 point) {
+	vec2 center = pointPair.xy;
+
+	if (method == 1) {
+		return length(point - center) - pointPair.z;
+	} else if (method == 2) {
+		vec2 p1 = pointPair.xy;
+		vec2 p2 = pointPair.zw;
 
 		vec2 pa = point - p1, ba = p2 - p1;
 		float h = clamp( dot(pa,ba) / dot(ba,ba), 0.0, 1.0 );
-		float dist = length(pa - ba * h);
-
-		if (dist <= vertexParameters.x) {
-			FragColor = vertexColor;
-		} else {
-			FragColor = vec4(0.0, 1.0, 0.0, 0.0);
-		}
-	} else if (vertexDrawMethod == 3) {
+		return length(pa - ba * h) - parameters.x;
+	} else if (method == 3) {
 		const vec3 k = vec3(-0.9238795325, 0.3826834323, 0.4142135623 );
-		float r = 5;
-		vec2 p = vertexPointPair.xy;
+		float r = pointPair.z;
+		vec2 p = point - center;
 		p = abs(p);
 		p -= 2.0*min(dot(vec2( k.x,k.y),p),0.0)*vec2( k.x,k.y);
 		p -= 2.0*min(dot(vec2(-k.x,k.y),p),0.0)*vec2(-k.x,k.y);
 		p -= vec2(clamp(p.x, -k.z*r, k.z*r), r);
-		float dist = length(p)*sign(p.y);
+		return length(p)*sign(p.y);
+	} else if (method == 4) {
+		return sdRoundedBox(point - center, pointPair.zw, parameters.x);
+	} else if (method == 5) {
+		return abs(length(point - center) - pointPair.z) - parameters.x / 2.0;
+	} else if (method == 6) {
+		float start = parameters.y;
+		float end = parameters.z;
+		// Turn the middle of the arc to face up
+		vec2 p = rotate(point - center, PI / 2.0 - (start + end) / 2.0);
+		return sdArc(p, (end - start) / 2.0, pointPair.z, parameters.x / 2.0);
+	} else if (method == 7) {
+		return sdTriangle(point, pointPair.xy, pointPair.zw, parameters.xy);
+	} else if (method == 8) {
+		return sdEllipse(point - center, pointPair.zw);
+	} else {
+		return sdPolygon(point - center, pointPair.z, parameters.x);
+	}
+}
+
+float composedDistance(vec2 point) {
+	float stack[8];
+	int top = 0;
+
+	for (int i = 0; i < ShapeLength; i++) {
+		vec4 head = Shape[i * 5];
+		int op = int(head.x);
 
-		if (dist <= 0.0) {
-			FragColor = vertexColor;
+		if (op == 0) {
+			vec4 matrix = Shape[i * 5 + 3];
+			vec2 local = vec2(dot(matrix.xy, point), dot(matrix.zw, point)) + Shape[i * 5 + 4].xy;
+			stack[top] = shapeDistance(int(head.y), Shape[i * 5 + 1], Shape[i * 5 + 2], local) * head.w;
+			top++;
+			continue;
+		}
+
+		float a = stack[top - 2];
+		float b = stack[top - 1];
+		top--;
+
+		if (op == 1) {
+			stack[top - 1] = min(a, b);
+		} else if (op == 2) {
+			stack[top - 1] = max(a, -b);
+		} else if (op == 3) {
+			stack[top - 1] = max(a, b);
 		} else {
-			FragColor = vec4(0.0, 0.0, 0.0, 0.0);
+			float k = max(head.z, 0.0001);
+			float h = clamp(0.5 + 0.5 * (b - a) / k, 0.0, 1.0);
+			stack[top - 1] = mix(b, a, h) - k * h * (1.0 - h);
 		}
-	} else {
+	}
+
+	return stack[0];
+}
+
+// Distance to the edge of what we're drawing
+float distance(vec2 point) {
+	if (vertexDrawMethod == 10) {
+		return composedDistance(point);
+	}
+
+	return shapeDistance(vertexDrawMethod, vertexPointPair, vertexParameters, point);
+}
+
+// a over b
+vec4 over(vec4 a, vec4 b) {
+	float alpha = a.a + b.a * (1.0 - a.a);
+	if (alpha == 0.0) {
+		return vec4(0.0);
+	}
+
+	vec3 color = (a.rgb * a.a + b.rgb * b.a * (1.0 - a.a)) / alpha;
+	return vec4(color, alpha);
+}
+
+void main() {
+	if (vertexDrawMethod < 1 || vertexDrawMethod > 10) {
 		FragColor = vec4(0.0, 0.0, 1.0, 1.0);
+		return;
+	}
+
+	vec2 point = gl_FragCoord.xy;
+	float dist = distance(point);
+	// How far a pixel is in distance, so edges fade over one pixel at any size
+	float aa = max(fwidth(dist), 0.0001) * 0.5;
+
+	float fill = 1.0 - smoothstep(-aa, aa, dist);
+	vec4 fillColor = vertexColor;
+	if (Gradient) {
+		fillColor = gradient((point - GradientBounds.xy) / GradientBounds.zw);
+	}
+
+	vec4 color = vec4(fillColor.rgb, fillColor.a * fill);
+
+	// The stroke is centered on the edge
+	if (StrokeWidth > 0.0) {
+		float stroke = 1.0 - smoothstep(-aa, aa, abs(dist) - StrokeWidth / 2.0);
+		color = over(vec4(StrokeColor.rgb, StrokeColor.a * stroke), color);
+	}
+
+	if (ShadowColor.a > 0.0) {
+		float shadow_dist = distance(point - ShadowOffset) - StrokeWidth / 2.0;
+		float shadow = 1.0 - smoothstep(-ShadowSoftness - aa, ShadowSoftness + aa, shadow_dist);
+		color = over(color, vec4(ShadowColor.rgb, ShadowColor.a * shadow));
+	}
+
+	FragColor = color;
+
+	if (DiscardTransparent && FragColor.a == 0.0) {
+		discard;
 	}
 }
@@ -0,0 +1,24 @@
+#version 410
+layout (location = 0) in vec2 ModelPosition;
+
+// One of each of these for every shape in the batch. Bounds is the center
+// and half the size of the quad in OpenGL coordinates.
+layout (location = 1) in vec4 Bounds;
+layout (location = 2) in vec4 PointPair;
+layout (location = 3) in vec4 Parameters;
+layout (location = 4) in vec4 Color;
+layout (location = 5) in float DrawMethod;
+
+out vec4 vertexColor;
+out vec4 vertexPointPair;
+out vec4 vertexParameters;
+flat out int vertexDrawMethod;
+
+void main() {
+	vertexColor = Color;
+	vertexPointPair = PointPair;
+	vertexParameters = Parameters;
+	vertexDrawMethod = int(DrawMethod);
+
+	gl_Position = vec4((ModelPosition * Bounds.zw) + Bounds.xy, 0.0, 1.0);
+}
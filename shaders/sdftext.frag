@@ -0,0 +1,56 @@
+#version 410
+out mediump vec4 FragColor;
+
+in mediump vec2 TexCoord;
+in mediump vec4 VertexColor;
+
+// Distance field atlas. Alpha is 0.5 on the edge of the glyph and goes up
+// going inside of it.
+uniform sampler2D Texture;
+uniform vec4 OutlineColor;
+// Distances are in the atlas' units, where 0.5 is as far as the field goes
+uniform float OutlineWidth;
+uniform vec4 GlowColor;
+uniform float GlowWidth;
+// Extra blur on the edge, for shadows
+uniform float Softness;
+// Set while drawing clipping masks so see-through pixels don't count
+uniform bool DiscardTransparent;
+
+// a over b
+vec4 over(vec4 a, vec4 b) {
+    float alpha = a.a + b.a * (1.0 - a.a);
+    if (alpha == 0.0) {
+        return vec4(0.0);
+    }
+
+    vec3 color = (a.rgb * a.a + b.rgb * b.a * (1.0 - a.a)) / alpha;
+    return vec4(color, alpha);
+}
+
+void main() {
+    float dist = texture(Texture, TexCoord).a;
+    // Half a screen pixel worth of distance, so edges are smooth at any scale
+    float aa = max(fwidth(dist) * 0.5, 0.0001);
+
+    float fill = smoothstep(0.5 - aa - Softness, 0.5 + aa, dist);
+    vec4 color = vec4(VertexColor.rgb, VertexColor.a * fill);
+
+    if (OutlineWidth > 0.0) {
+        float edge = 0.5 - OutlineWidth;
+        float outline = smoothstep(edge - aa, edge + aa, dist);
+        color = over(color, vec4(OutlineColor.rgb, OutlineColor.a * outline));
+    }
+
+    if (GlowWidth > 0.0) {
+        float edge = 0.5 - OutlineWidth;
+        float glow = smoothstep(edge - GlowWidth, edge, dist);
+        color = over(color, vec4(GlowColor.rgb, GlowColor.a * glow));
+    }
+
+    FragColor = color;
+
+    if (DiscardTransparent && FragColor.a == 0.0) {
+        discard;
+    }
+}
@@ -3,18 +3,48 @@ out mediump vec4 FragColor;
 
 in mediump vec2 TexCoord;
 in mediump vec4 VertexColor;
+in mediump vec2 LocalPosition;
 flat in int ColorTex;
 
 uniform sampler2D Texture;
+// Set while drawing clipping masks so see-through pixels don't count
+uniform bool DiscardTransparent;
+
+// Set while drawing tiled parts of a nine slice. The texture coordinate
+// wraps around inside of TileRegion, which is the offset and size of the part
+// of the texture to repeat, so one rectangle can hold many tiles.
+uniform bool Tiled;
+uniform vec4 TileRegion;
+
+#include "gradient.glsl"
+
+// Wrapping makes the coordinate jump at every tile edge, so sample with
+// the gradient of the unwrapped one or the smallest mip shows up as a seam
+vec4 sampleTexture() {
+    if (Tiled) {
+        vec2 coord = TileRegion.xy + fract(TexCoord) * TileRegion.zw;
+        vec2 dx = dFdx(TexCoord) * TileRegion.zw;
+        vec2 dy = dFdy(TexCoord) * TileRegion.zw;
+        return textureGrad(Texture, coord, dx, dy);
+    }
+
+    return texture(Texture, TexCoord);
+}
 
 void main() {
     if (ColorTex == 1) {
         //FragColor = vec4(0.0, 0.0, 1.0, 1.0);
-        FragColor = texture(Texture, TexCoord) * VertexColor;
+        FragColor = sampleTexture() * VertexColor;
     } else if (ColorTex == 2) {//FragColor = vec4(0.0, 1.0, 0.0, 1.0);
         FragColor = VertexColor;
+    } else if (ColorTex == 3) {
+        FragColor = gradient(LocalPosition);
     } else {
         //ragColor = vec4(1.0, 0.0, 0.0, 1.0);
-        FragColor = texture(Texture, TexCoord);
+        FragColor = sampleTexture();
+    }
+
+    if (DiscardTransparent && FragColor.a == 0.0) {
+        discard;
     }
 }
\ No newline at end of file
@@ -4,16 +4,21 @@ layout (location = 1) in vec2 aTexCoord;
 
 out mediump vec4 VertexColor;
 out mediump vec2 TexCoord;
+// Where we are in the rectangle, from 0 at the bottom left to 1 at the top right
+out mediump vec2 LocalPosition;
 flat out int ColorTex;
 
 uniform int ColorTexture;
 uniform vec2 WorldPosition;
 uniform vec2 Scale;
 uniform vec4 Color;
+uniform vec2 TextureOffset;
+uniform vec2 TextureScale;
 
 void main() {
     gl_Position = vec4((ModelPosition * Scale) + WorldPosition, 0.0, 1.0);
-    TexCoord = aTexCoord;
+    TexCoord = TextureOffset + (aTexCoord * TextureScale);
+    LocalPosition = ModelPosition * 0.5 + 0.5;
     VertexColor = Color;
     ColorTex = ColorTexture;
 }
\ No newline at end of file
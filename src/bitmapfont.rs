@@ -0,0 +1,338 @@
+use std::{
+	collections::HashMap,
+	fs,
+	path::{Path, PathBuf},
+};
+
+use fontdue::Metrics;
+use image::io::Reader as ImageReader;
+use thiserror::Error;
+
+use crate::{
+	gl::{Filter, OpenGl, Texture},
+	smittenfont::CachedGlyph,
+	Vec2,
+};
+
+/// A font that's already been drawn into images, either as an AngelCode
+/// BMFont or as a grid of same sized cells.
+pub struct BitmapFont {
+	/// The size, in pixels, the glyphs were drawn at
+	pub size: f32,
+	/// Distance between baselines of consecutive lines, in pixels
+	pub line_height: f32,
+	chars: HashMap<char, u16>,
+	glyphs: Vec<BitmapGlyph>,
+	kerning: HashMap<(u16, u16), f32>,
+	/// The textures and their width and height
+	pages: Vec<(Texture, u32, u32)>,
+}
+
+/// Where a glyph is on its page and how it sits on the line, in pixels
+#[derive(Copy, Clone, Debug)]
+struct BitmapGlyph {
+	page: usize,
+	x: u32,
+	y: u32,
+	width: u32,
+	height: u32,
+	/// Offset of the left of the image from the pen
+	xmin: f32,
+	/// Offset of the bottom of the image from the baseline. Y goes up.
+	ymin: f32,
+	advance: f32,
+}
+
+impl BitmapFont {
+	/// Load an AngelCode BMFont, in either the text or the XML format. The page
+	/// images are looked for next to the `.fnt` file.
+	pub fn bmfont<P: AsRef<Path>>(
+		gl: &OpenGl,
+		path: P,
+		filter: Filter,
+	) -> Result<Self, BitmapFontError> {
+		let path = path.as_ref();
+		let description = fs::read_to_string(path)?;
+		let directory = path.parent().unwrap_or_else(|| Path::new(""));
+
+		let mut size = None;
+		let mut line_height = None;
+		let mut base = None;
+		let mut page_files: Vec<(usize, PathBuf)> = vec![];
+		let mut chars = HashMap::new();
+		let mut glyphs = vec![];
+		let mut kerning_pairs = vec![];
+
+		for line in description.lines() {
+			let (tag, attributes) = match parse_tag(line) {
+				Some(tag) => tag,
+				None => continue,
+			};
+			let number = |key: &str| -> Result<f32, BitmapFontError> {
+				attributes
+					.get(key)
+					.ok_or_else(|| BitmapFontError::MissingAttribute(tag.clone(), key.into()))?
+					.parse()
+					.map_err(|_| BitmapFontError::InvalidAttribute(tag.clone(), key.into()))
+			};
+
+			match tag.as_str() {
+				// The size is negative if it's matching the character height
+				// rather than the cell height.
+				"info" => size = Some(number("size")?.abs()),
+				"common" => {
+					line_height = Some(number("lineHeight")?);
+					base = Some(number("base")?);
+				}
+				"page" => {
+					let file = attributes.get("file").ok_or_else(|| {
+						BitmapFontError::MissingAttribute(tag.clone(), "file".into())
+					})?;
+					page_files.push((number("id")? as usize, directory.join(file)));
+				}
+				"char" => {
+					let base = base.ok_or(BitmapFontError::MissingCommon)?;
+					let id = number("id")? as u32;
+					let ch = match char::from_u32(id) {
+						Some(ch) => ch,
+						None => continue,
+					};
+
+					let height = number("height")?;
+					chars.insert(ch, glyphs.len() as u16);
+					glyphs.push(BitmapGlyph {
+						page: number("page")? as usize,
+						x: number("x")? as u32,
+						y: number("y")? as u32,
+						width: number("width")? as u32,
+						height: height as u32,
+						xmin: number("xoffset")?,
+						// BMFont measures down from the top of the line
+						ymin: base - number("yoffset")? - height,
+						advance: number("xadvance")?,
+					});
+				}
+				"kerning" => {
+					kerning_pairs.push((number("first")?, number("second")?, number("amount")?))
+				}
+				_ => (),
+			}
+		}
+
+		let line_height = line_height.ok_or(BitmapFontError::MissingCommon)?;
+		if glyphs.is_empty() {
+			return Err(BitmapFontError::NoCharacters);
+		}
+
+		// Pages are looked up by their position, so every page a glyph is on
+		// has to be there with nothing missing before it
+		page_files.sort_by_key(|(id, _)| *id);
+		for glyph in &glyphs {
+			if page_files.get(glyph.page).map(|(id, _)| *id) != Some(glyph.page) {
+				return Err(BitmapFontError::MissingPage(glyph.page));
+			}
+		}
+		let pages = page_files
+			.into_iter()
+			.map(|(_, file)| load_page(gl, file, filter))
+			.collect::<Result<_, _>>()?;
+
+		let mut kerning = HashMap::new();
+		for (first, second, amount) in kerning_pairs {
+			let index = |id: f32| char::from_u32(id as u32).and_then(|ch| chars.get(&ch));
+
+			if let (Some(first), Some(second)) = (index(first), index(second)) {
+				kerning.insert((*first, *second), amount);
+			}
+		}
+
+		Ok(Self {
+			size: size.unwrap_or(line_height),
+			line_height,
+			chars,
+			glyphs,
+			kerning,
+			pages,
+		})
+	}
+
+	/// Load an image cut into `cell_width` by `cell_height` cells, one for
+	/// each character of `charset`, left to right and then top to bottom. The
+	/// baseline is the bottom of the cells.
+	pub fn grid<P: AsRef<Path>>(
+		gl: &OpenGl,
+		path: P,
+		charset: &str,
+		cell_width: u32,
+		cell_height: u32,
+		filter: Filter,
+	) -> Result<Self, BitmapFontError> {
+		if charset.is_empty() {
+			return Err(BitmapFontError::NoCharacters);
+		}
+
+		if cell_width == 0 || cell_height == 0 {
+			return Err(BitmapFontError::EmptyCell);
+		}
+
+		let page = load_page(gl, path, filter)?;
+		let columns = page.1 / cell_width;
+		let rows = page.2 / cell_height;
+
+		if columns == 0 || rows == 0 {
+			return Err(BitmapFontError::CellTooLarge);
+		}
+
+		let count = charset.chars().count();
+		if count > (columns * rows) as usize {
+			return Err(BitmapFontError::TooManyCharacters(
+				count,
+				(columns * rows) as usize,
+			));
+		}
+
+		let mut chars = HashMap::new();
+		let mut glyphs = vec![];
+		for (idx, ch) in charset.chars().enumerate() {
+			chars.insert(ch, glyphs.len() as u16);
+			glyphs.push(BitmapGlyph {
+				page: 0,
+				x: (idx as u32 % columns) * cell_width,
+				y: (idx as u32 / columns) * cell_height,
+				width: cell_width,
+				height: cell_height,
+				xmin: 0.0,
+				ymin: 0.0,
+				advance: cell_width as f32,
+			});
+		}
+
+		Ok(Self {
+			size: cell_height as f32,
+			line_height: cell_height as f32,
+			chars,
+			glyphs,
+			kerning: HashMap::new(),
+			pages: vec![page],
+		})
+	}
+
+	pub fn lookup(&self, ch: char) -> Option<u16> {
+		self.chars.get(&ch).copied()
+	}
+
+	/// Metrics of the glyph when the font's drawn `size` pixels tall
+	pub fn metrics(&self, index: u16, size: f32) -> Metrics {
+		let glyph = &self.glyphs[index as usize];
+		let scale = size / self.size;
+
+		Metrics {
+			xmin: (glyph.xmin * scale).round() as i32,
+			ymin: (glyph.ymin * scale).round() as i32,
+			width: (glyph.width as f32 * scale).round() as usize,
+			height: (glyph.height as f32 * scale).round() as usize,
+			advance_width: glyph.advance * scale,
+			..Default::default()
+		}
+	}
+
+	pub fn kern(&self, left: u16, right: u16, size: f32) -> f32 {
+		self.kerning
+			.get(&(left, right))
+			.map(|amount| amount * size / self.size)
+			.unwrap_or(0.0)
+	}
+
+	/// Where the glyph is on its page
+	pub fn glyph(&self, index: u16) -> CachedGlyph {
+		let glyph = &self.glyphs[index as usize];
+		let (_, width, height) = &self.pages[glyph.page];
+		let (width, height) = (*width as f32, *height as f32);
+
+		CachedGlyph {
+			page: glyph.page,
+			texture_position: Vec2::new(glyph.x as f32 / width, glyph.y as f32 / height),
+			texture_dimensions: Vec2::new(glyph.width as f32 / width, glyph.height as f32 / height),
+			offset: Vec2::new(glyph.xmin, glyph.ymin),
+			size: Vec2::new(glyph.width as f32, glyph.height as f32),
+		}
+	}
+
+	pub fn bind_page(&self, gl: &OpenGl, page: usize) {
+		unsafe { self.pages[page].0.bind(gl) }
+	}
+}
+
+/// Load an image into a texture, along with its width and height
+fn load_page<P: AsRef<Path>>(
+	gl: &OpenGl,
+	path: P,
+	filter: Filter,
+) -> Result<(Texture, u32, u32), BitmapFontError> {
+	let image = ImageReader::open(path)?.decode()?.to_rgba8();
+	let (width, height) = image.dimensions();
+
+	let texture = Texture::atlas(gl, width as usize, height as usize, filter);
+	texture.update(gl, 0, 0, width as usize, height as usize, image.as_raw());
+
+	Ok((texture, width, height))
+}
+
+/// Split a line of a BMFont description into its tag and attributes. The
+/// text format looks like `char id=65 x=0` and the XML one like
+/// `<char id="65" x="0"/>`, so the same thing reads both.
+fn parse_tag(line: &str) -> Option<(String, HashMap<String, String>)> {
+	let line = line
+		.trim()
+		.trim_start_matches('<')
+		.trim_end_matches('>')
+		.trim_end_matches('/');
+
+	let (tag, mut rest) = match line.split_once(char::is_whitespace) {
+		Some((tag, rest)) => (tag, rest),
+		None => (line, ""),
+	};
+
+	if tag.is_empty() || tag.starts_with('?') || tag.starts_with('!') {
+		return None;
+	}
+
+	let mut attributes = HashMap::new();
+	while let Some((key, after)) = rest.split_once('=') {
+		let after = after.trim_start();
+
+		let (value, after) = match after.strip_prefix('"') {
+			Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+			None => after.split_once(char::is_whitespace).unwrap_or((after, "")),
+		};
+
+		attributes.insert(key.trim().to_string(), value.to_string());
+		rest = after;
+	}
+
+	Some((tag.to_string(), attributes))
+}
+
+#[derive(Debug, Error)]
+pub enum BitmapFontError {
+	#[error("Could not read the font: {0}")]
+	Io(#[from] std::io::Error),
+	#[error("Could not load a page of the font: {0}")]
+	Image(#[from] image::ImageError),
+	#[error("The {0} tag is missing its {1}")]
+	MissingAttribute(String, String),
+	#[error("The {1} of the {0} tag could not be parsed")]
+	InvalidAttribute(String, String),
+	#[error("The font has no common tag before its characters")]
+	MissingCommon,
+	#[error("The cells are bigger than the image")]
+	CellTooLarge,
+	#[error("The cells have no width or height")]
+	EmptyCell,
+	#[error("There are {0} characters but the image only has room for {1}")]
+	TooManyCharacters(usize, usize),
+	#[error("The font has no characters")]
+	NoCharacters,
+	#[error("A character is on page {0} but the font has no such page")]
+	MissingPage(usize),
+}
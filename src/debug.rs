@@ -0,0 +1,148 @@
+use std::cell::{Cell, RefCell};
+
+use crate::{Color, FontId, Vec2};
+
+/// Shapes and labels for seeing what's going on, drawn over everything else
+/// when the frame is swapped and then forgotten. Get it with
+/// [crate::Smitten::debug].
+///
+/// Positions are in murs like everything else, but render targets, clips,
+/// and masks don't apply. Without the `debug-draw` feature, which is on by
+/// default, nothing here draws anything.
+///
+/// ```ignore
+/// smitty.debug().arrow(body.position, body.position + body.velocity, Color::RED);
+/// smitty.debug().text(body.position, format!("{:.1}", body.speed()), Color::WHITE);
+/// ```
+#[derive(Default)]
+pub struct DebugDraw {
+	#[cfg_attr(not(feature = "debug-draw"), allow(dead_code))]
+	shapes: RefCell<Vec<DebugShape>>,
+	font: Cell<Option<FontId>>,
+}
+
+#[cfg_attr(not(feature = "debug-draw"), allow(dead_code))]
+pub(crate) enum DebugShape {
+	Line {
+		start: Vec2,
+		end: Vec2,
+		color: Color,
+	},
+	Rect {
+		center: Vec2,
+		size: Vec2,
+		color: Color,
+	},
+	Circle {
+		center: Vec2,
+		radius: f32,
+		color: Color,
+	},
+	Arrow {
+		start: Vec2,
+		end: Vec2,
+		color: Color,
+	},
+	Text {
+		position: Vec2,
+		text: String,
+		color: Color,
+	},
+}
+
+impl DebugDraw {
+	pub fn line<S, E, C>(&self, start: S, end: E, color: C)
+	where
+		S: Into<Vec2>,
+		E: Into<Vec2>,
+		C: Into<Color>,
+	{
+		self.push(|| DebugShape::Line {
+			start: start.into(),
+			end: end.into(),
+			color: color.into(),
+		})
+	}
+
+	/// The outline of a rectangle at `center` that's `size` murs big
+	pub fn rect<P, D, C>(&self, center: P, size: D, color: C)
+	where
+		P: Into<Vec2>,
+		D: Into<Vec2>,
+		C: Into<Color>,
+	{
+		self.push(|| DebugShape::Rect {
+			center: center.into(),
+			size: size.into(),
+			color: color.into(),
+		})
+	}
+
+	/// The outline of a circle
+	pub fn circle<P, C>(&self, center: P, radius: f32, color: C)
+	where
+		P: Into<Vec2>,
+		C: Into<Color>,
+	{
+		self.push(|| DebugShape::Circle {
+			center: center.into(),
+			radius,
+			color: color.into(),
+		})
+	}
+
+	/// A line with a head on the `end`
+	pub fn arrow<S, E, C>(&self, start: S, end: E, color: C)
+	where
+		S: Into<Vec2>,
+		E: Into<Vec2>,
+		C: Into<Color>,
+	{
+		self.push(|| DebugShape::Arrow {
+			start: start.into(),
+			end: end.into(),
+			color: color.into(),
+		})
+	}
+
+	/// A label with its top left corner at `position`. Nothing is drawn until there's a font
+	/// to draw with, see [DebugDraw::font].
+	pub fn text<P, S, C>(&self, position: P, text: S, color: C)
+	where
+		P: Into<Vec2>,
+		S: Into<String>,
+		C: Into<Color>,
+	{
+		self.push(|| DebugShape::Text {
+			position: position.into(),
+			text: text.into(),
+			color: color.into(),
+		})
+	}
+
+	/// Set the font labels are written in
+	pub fn font(&self, font: FontId) {
+		self.font.set(Some(font));
+	}
+
+	#[cfg(feature = "debug-draw")]
+	pub(crate) fn label_font(&self) -> Option<FontId> {
+		self.font.get()
+	}
+
+	#[cfg(feature = "debug-draw")]
+	/// Everything that's been drawn since the last time this was called
+	pub(crate) fn take(&self) -> Vec<DebugShape> {
+		self.shapes.take()
+	}
+
+	// Only build the shape if it's going to be drawn, so there's nothing left
+	// of debug drawing without the feature.
+	#[cfg(feature = "debug-draw")]
+	fn push<F: FnOnce() -> DebugShape>(&self, f: F) {
+		self.shapes.borrow_mut().push(f());
+	}
+
+	#[cfg(not(feature = "debug-draw"))]
+	fn push<F: FnOnce() -> DebugShape>(&self, _f: F) {}
+}
@@ -0,0 +1,76 @@
+use glow::{HasContext, NativeFramebuffer, NativeRenderbuffer};
+
+use crate::PixelSize;
+
+use super::{OpenGl, Texture};
+
+/// An offscreen render target. Everything drawn while this is bound ends up
+/// in the attached texture instead of the window.
+pub struct Framebuffer {
+	framebuffer: NativeFramebuffer,
+	/// Stencil (and depth) attachment so clipping masks work in here, too
+	renderbuffer: NativeRenderbuffer,
+	pub size: PixelSize,
+}
+
+impl Framebuffer {
+	/// Creates a framebuffer and the texture it renders into. The texture is
+	/// returned separately so it can be handed out like any other texture.
+	pub fn new(ogl: &OpenGl, size: PixelSize) -> (Self, Texture) {
+		let texture = Texture::empty(ogl, size.width as usize, size.height as usize);
+
+		let gl = ogl.gl();
+		let (framebuffer, renderbuffer) = unsafe {
+			let fbo = gl.create_framebuffer().unwrap();
+			gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+			gl.framebuffer_texture_2d(
+				glow::FRAMEBUFFER,
+				glow::COLOR_ATTACHMENT0,
+				glow::TEXTURE_2D,
+				Some(texture.native()),
+				0,
+			);
+
+			let rbo = gl.create_renderbuffer().unwrap();
+			gl.bind_renderbuffer(glow::RENDERBUFFER, Some(rbo));
+			gl.renderbuffer_storage(
+				glow::RENDERBUFFER,
+				glow::DEPTH24_STENCIL8,
+				size.width as i32,
+				size.height as i32,
+			);
+			gl.framebuffer_renderbuffer(
+				glow::FRAMEBUFFER,
+				glow::DEPTH_STENCIL_ATTACHMENT,
+				glow::RENDERBUFFER,
+				Some(rbo),
+			);
+			gl.bind_renderbuffer(glow::RENDERBUFFER, None);
+
+			if gl.check_framebuffer_status(glow::FRAMEBUFFER) != glow::FRAMEBUFFER_COMPLETE {
+				panic!("Framebuffer is incomplete");
+			}
+
+			gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+			(fbo, rbo)
+		};
+
+		(
+			Self {
+				framebuffer,
+				renderbuffer,
+				size,
+			},
+			texture,
+		)
+	}
+
+	pub fn native(&self) -> NativeFramebuffer {
+		self.framebuffer
+	}
+
+	pub unsafe fn delete(&self, ogl: &OpenGl) {
+		ogl.gl().delete_framebuffer(self.framebuffer);
+		ogl.gl().delete_renderbuffer(self.renderbuffer);
+	}
+}
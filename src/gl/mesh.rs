@@ -0,0 +1,51 @@
+use glow::{Buffer, HasContext, VertexArray};
+
+use crate::Vec2;
+
+/// Triangles that change every time they're drawn, like tessellated paths
+pub struct Mesh {
+	vao: VertexArray,
+	vbo: Buffer,
+}
+
+impl Mesh {
+	pub fn new(gl: &glow::Context) -> Self {
+		unsafe {
+			let vao = gl.create_vertex_array().unwrap();
+			gl.bind_vertex_array(Some(vao));
+
+			let vbo = gl.create_buffer().unwrap();
+			gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbo));
+			gl.vertex_attrib_pointer_f32(0, 2, glow::FLOAT, false, 2 * 4, 0);
+			gl.enable_vertex_attrib_array(0);
+
+			gl.bind_buffer(glow::ARRAY_BUFFER, None);
+			gl.bind_vertex_array(None);
+
+			Self { vao, vbo }
+		}
+	}
+
+	/// Draw every three of `vertices`, which are in OpenGL coordinates, as a
+	/// triangle
+	pub unsafe fn draw(&self, gl: &glow::Context, vertices: &[Vec2]) {
+		let buffer: Vec<u8> = vertices
+			.iter()
+			.flat_map(|v| [v.x.to_le_bytes(), v.y.to_le_bytes()])
+			.flatten()
+			.collect();
+
+		gl.bind_vertex_array(Some(self.vao));
+		gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbo));
+		gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, &buffer, glow::STREAM_DRAW);
+		gl.bind_buffer(glow::ARRAY_BUFFER, None);
+
+		gl.draw_arrays(glow::TRIANGLES, 0, vertices.len() as i32);
+		gl.bind_vertex_array(None);
+	}
+
+	pub unsafe fn delete(&self, gl: &glow::Context) {
+		gl.delete_vertex_array(self.vao);
+		gl.delete_buffer(self.vbo);
+	}
+}
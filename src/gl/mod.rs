@@ -1,13 +1,20 @@
+mod framebuffer;
+mod mesh;
 mod rectangle;
+mod sdfbatch;
+mod sdfshape;
 mod texture;
 mod transform;
 
+pub use framebuffer::Framebuffer;
 pub use rectangle::Rectangle;
-pub use texture::Texture;
+pub use sdfshape::{SdfShape, SdfShapeError};
+pub use texture::{Filter, Texture};
 pub use transform::Transform;
 
 use std::{
 	cell::{Cell, RefCell},
+	f32::consts::TAU,
 	path::Path as FilePath,
 	rc::Rc,
 };
@@ -15,16 +22,38 @@ use std::{
 use glow::{HasContext, Program};
 use glutin::{window::Window, ContextWrapper, PossiblyCurrent};
 
-use crate::{Color, Vec2};
+use crate::{Color, PixelSize, Vec2};
+
+use mesh::Mesh;
+use sdfbatch::{SdfBatch, INSTANCE_FLOATS};
+
+/// The vertex shader of our texture program. User shaders that only care
+/// about the fragment stage can be paired with it.
+pub const TEXTURE_VERTEX_SHADER: &str = include_str!("../../shaders/texture.vert");
+
+/// Put the gradient function where a shader has `#include "gradient.glsl"`,
+/// since GLSL can't do that itself
+fn with_gradient(source: &str) -> String {
+	source.replace(
+		"#include \"gradient.glsl\"",
+		include_str!("../../shaders/gradient.glsl"),
+	)
+}
 
 pub struct OpenGl {
 	gl: Rc<glow::Context>,
 	pub transform: Transform,
 	program: Program,
 	sdf: Program,
+	sdf_batch: Program,
+	sdf_text: Program,
 	clear_color: Color,
 	draw_rect: Rectangle,
+	mesh: Mesh,
+	sdf_instances: SdfBatch,
 	bound_program: Cell<Program>,
+	texture_transform: Cell<(Vec2, Vec2)>,
+	blend_mode: Cell<BlendMode>,
 }
 
 impl OpenGl {
@@ -53,8 +82,8 @@ impl OpenGl {
 		let program = unsafe {
 			Self::create_program(
 				&gl,
-				include_str!("../../shaders/texture.vert"),
-				include_str!("../../shaders/texture.frag"),
+				TEXTURE_VERTEX_SHADER,
+				&with_gradient(include_str!("../../shaders/texture.frag")),
 			)
 		};
 
@@ -62,24 +91,51 @@ impl OpenGl {
 			Self::create_program(
 				&gl,
 				include_str!("../../shaders/sdf.vert"),
-				include_str!("../../shaders/sdf.frag"),
+				&with_gradient(include_str!("../../shaders/sdf.frag")),
+			)
+		};
+
+		let sdf_batch = unsafe {
+			Self::create_program(
+				&gl,
+				include_str!("../../shaders/sdfbatch.vert"),
+				&with_gradient(include_str!("../../shaders/sdf.frag")),
+			)
+		};
+
+		let sdf_text = unsafe {
+			Self::create_program(
+				&gl,
+				TEXTURE_VERTEX_SHADER,
+				include_str!("../../shaders/sdftext.frag"),
 			)
 		};
 
 		unsafe {
 			gl.use_program(Some(program));
+
+			let uniform_scale = gl.get_uniform_location(program, "TextureScale");
+			gl.uniform_2_f32(uniform_scale.as_ref(), 1.0, 1.0);
 		}
 
 		let draw_rect = Rectangle::new(&gl, (2.0, 2.0).into());
+		let mesh = Mesh::new(&gl);
+		let sdf_instances = SdfBatch::new(&gl);
 
 		Self {
 			gl: Rc::new(gl),
 			transform,
 			program,
 			sdf,
+			sdf_batch,
+			sdf_text,
 			clear_color: Color::rgba(0.0, 0.0, 0.0, 1.0),
 			draw_rect,
+			mesh,
+			sdf_instances,
 			bound_program: Cell::new(program),
+			texture_transform: Cell::new((Vec2::ZERO, Vec2::new(1.0, 1.0))),
+			blend_mode: Cell::new(BlendMode::Alpha),
 		}
 	}
 
@@ -94,7 +150,20 @@ impl OpenGl {
 	}
 
 	pub fn clear(&self) {
-		unsafe { self.gl.clear(glow::COLOR_BUFFER_BIT) }
+		unsafe {
+			self.gl
+				.clear(glow::COLOR_BUFFER_BIT | glow::STENCIL_BUFFER_BIT)
+		}
+	}
+
+	/// Clear to transparent black regardless of the clear color
+	pub fn clear_transparent(&self) {
+		let c = self.clear_color;
+		unsafe {
+			self.gl.clear_color(0.0, 0.0, 0.0, 0.0);
+			self.gl.clear(glow::COLOR_BUFFER_BIT);
+			self.gl.clear_color(c.r, c.g, c.b, c.a);
+		}
 	}
 
 	pub fn resized(&mut self, width: u32, height: u32) {
@@ -103,11 +172,33 @@ impl OpenGl {
 			.resized(glutin::dpi::PhysicalSize { width, height })
 	}
 
+	/// Bind a framebuffer to draw into, or the window if `None`, and set the
+	/// viewport to match its size.
+	pub fn bind_framebuffer(&self, framebuffer: Option<&Framebuffer>, size: PixelSize) {
+		unsafe {
+			self.gl
+				.bind_framebuffer(glow::FRAMEBUFFER, framebuffer.map(|fb| fb.native()));
+			self.gl
+				.viewport(0, 0, size.width as i32, size.height as i32);
+		}
+	}
+
 	unsafe fn create_program(
 		gl: &glow::Context,
 		vertex_source: &str,
 		fragment_source: &str,
 	) -> Program {
+		match Self::try_create_program(gl, vertex_source, fragment_source) {
+			Ok(program) => program,
+			Err(e) => panic!("{}", e),
+		}
+	}
+
+	unsafe fn try_create_program(
+		gl: &glow::Context,
+		vertex_source: &str,
+		fragment_source: &str,
+	) -> Result<Program, String> {
 		let program = gl.create_program().expect("Failed to create program");
 
 		let shader_soruces = [
@@ -122,7 +213,15 @@ impl OpenGl {
 			gl.compile_shader(shader);
 
 			if !gl.get_shader_compile_status(shader) {
-				panic!("{}", gl.get_shader_info_log(shader));
+				let log = gl.get_shader_info_log(shader);
+
+				gl.delete_shader(shader);
+				for shader in shaders {
+					gl.delete_shader(shader);
+				}
+				gl.delete_program(program);
+
+				return Err(log);
 			}
 
 			gl.attach_shader(program, shader);
@@ -130,9 +229,7 @@ impl OpenGl {
 		}
 
 		gl.link_program(program);
-		if !gl.get_program_link_status(program) {
-			panic!("{}", gl.get_program_info_log(program));
-		}
+		let linked = gl.get_program_link_status(program);
 
 		// Shaders are compiled and linked with the program, we don't need them anymore
 		for shader in shaders {
@@ -140,7 +237,32 @@ impl OpenGl {
 			gl.delete_shader(shader);
 		}
 
-		program
+		if !linked {
+			let log = gl.get_program_info_log(program);
+			gl.delete_program(program);
+
+			return Err(log);
+		}
+
+		Ok(program)
+	}
+
+	/// Compile and link a program from user provided shader sources. Unlike
+	/// our own programs, failing here is not fatal.
+	pub fn create_user_program(
+		&self,
+		vertex_source: &str,
+		fragment_source: &str,
+	) -> Result<Program, String> {
+		unsafe { Self::try_create_program(&self.gl, vertex_source, fragment_source) }
+	}
+
+	pub fn delete_user_program(&self, program: Program) {
+		if self.bound_program.get() == program {
+			self.bind_program();
+		}
+
+		unsafe { self.gl.delete_program(program) }
 	}
 
 	pub fn set_color_uniform(&self, color: Color) {
@@ -161,6 +283,15 @@ impl OpenGl {
 		}
 	}
 
+	pub fn bind_user_program(&self, program: Program) {
+		if self.bound_program.get() != program {
+			unsafe {
+				self.gl.use_program(Some(program));
+			}
+			self.bound_program.set(program);
+		}
+	}
+
 	pub fn bind_sdf(&self) {
 		if self.bound_program.get() != self.sdf {
 			unsafe {
@@ -170,6 +301,146 @@ impl OpenGl {
 		}
 	}
 
+	/// Only draw inside of the rectangle, which is `(x, y, width, height)` in
+	/// pixels from the bottom left. `None` draws everywhere.
+	pub fn set_scissor(&self, rect: Option<(i32, i32, i32, i32)>) {
+		unsafe {
+			match rect {
+				Some((x, y, width, height)) => {
+					self.gl.enable(glow::SCISSOR_TEST);
+					self.gl.scissor(x, y, width, height);
+				}
+				None => self.gl.disable(glow::SCISSOR_TEST),
+			}
+		}
+	}
+
+	/// Start drawing a clipping mask on top of the `depth` masks already
+	/// there. Nothing drawn until [OpenGl::finish_mask] shows up on screen, it
+	/// only marks where we're allowed to draw.
+	pub fn start_mask(&self, depth: u8) {
+		self.set_discard_transparent(true);
+
+		unsafe {
+			self.gl.enable(glow::STENCIL_TEST);
+			self.gl.color_mask(false, false, false, false);
+			self.gl.stencil_func(glow::EQUAL, depth as i32, 0xFF);
+			self.gl.stencil_op(glow::KEEP, glow::KEEP, glow::INCR);
+		}
+	}
+
+	/// Stop drawing the mask started with [OpenGl::start_mask] and clip to it
+	pub fn finish_mask(&self, depth: u8) {
+		self.set_discard_transparent(false);
+
+		unsafe { self.gl.color_mask(true, true, true, true) }
+		self.apply_mask(depth + 1);
+	}
+
+	/// Take away the top mask, leaving `depth - 1`. The mask is taken away
+	/// everywhere, so the scissor is turned off while we do it and set back
+	/// to `clip` after.
+	pub fn remove_mask(&self, depth: u8, clip: Option<(i32, i32, i32, i32)>) {
+		self.set_scissor(None);
+
+		unsafe {
+			self.gl.color_mask(false, false, false, false);
+			self.gl.stencil_func(glow::EQUAL, depth as i32, 0xFF);
+			self.gl.stencil_op(glow::KEEP, glow::KEEP, glow::DECR);
+		}
+
+		self.draw_fullscreen(self.program, false);
+
+		unsafe { self.gl.color_mask(true, true, true, true) }
+		self.apply_mask(depth - 1);
+		self.set_scissor(clip);
+	}
+
+	/// Only draw where `depth` masks overlap, or everywhere if it's zero
+	pub fn apply_mask(&self, depth: u8) {
+		unsafe {
+			if depth == 0 {
+				self.gl.disable(glow::STENCIL_TEST);
+			} else {
+				self.gl.enable(glow::STENCIL_TEST);
+				self.gl.stencil_func(glow::EQUAL, depth as i32, 0xFF);
+				self.gl.stencil_op(glow::KEEP, glow::KEEP, glow::KEEP);
+			}
+		}
+	}
+
+	fn set_discard_transparent(&self, discard: bool) {
+		unsafe {
+			for program in [self.program, self.sdf, self.sdf_batch, self.sdf_text] {
+				self.bind_user_program(program);
+
+				let uniform = self.gl.get_uniform_location(program, "DiscardTransparent");
+				self.gl.uniform_1_i32(uniform.as_ref(), discard as i32);
+			}
+		}
+	}
+
+	pub fn set_blend_mode(&self, mode: BlendMode) {
+		if self.blend_mode.get() == mode {
+			return;
+		}
+
+		unsafe {
+			match mode {
+				BlendMode::Replace => self.gl.disable(glow::BLEND),
+				_ => {
+					let (src, dst) = mode.factors();
+					self.gl.enable(glow::BLEND);
+					self.gl.blend_func(src, dst);
+				}
+			}
+		}
+
+		self.blend_mode.set(mode);
+	}
+
+	pub fn blend_mode(&self) -> BlendMode {
+		self.blend_mode.get()
+	}
+
+	/// Transforms the texture coordinates of the next draws. Coordinates are
+	/// computed as `offset + (coordinate * scale)`.
+	pub fn set_texture_transform(&self, offset: Vec2, scale: Vec2) {
+		if self.texture_transform.get() == (offset, scale) {
+			return;
+		}
+
+		self.bind_program();
+		unsafe {
+			let uniform_offset = self.gl.get_uniform_location(self.program, "TextureOffset");
+			let uniform_scale = self.gl.get_uniform_location(self.program, "TextureScale");
+			self.gl
+				.uniform_2_f32(uniform_offset.as_ref(), offset.x, offset.y);
+			self.gl
+				.uniform_2_f32(uniform_scale.as_ref(), scale.x, scale.y);
+		}
+
+		self.texture_transform.set((offset, scale));
+	}
+
+	/// Wrap the texture coordinates around inside of `region`, given as the
+	/// offset and size of it in texture coordinates. The texture transform
+	/// then says how many times it repeats. `None` stops tiling.
+	pub fn set_tile_region(&self, region: Option<(Vec2, Vec2)>) {
+		self.bind_program();
+		unsafe {
+			let uniform_tiled = self.gl.get_uniform_location(self.program, "Tiled");
+			self.gl
+				.uniform_1_i32(uniform_tiled.as_ref(), region.is_some() as i32);
+
+			if let Some((offset, size)) = region {
+				let uniform_region = self.gl.get_uniform_location(self.program, "TileRegion");
+				self.gl
+					.uniform_4_f32(uniform_region.as_ref(), offset.x, offset.y, size.x, size.y);
+			}
+		}
+	}
+
 	//TODO: gen- Make this an enum
 	pub fn set_texture_coloring_uniform(&self, value: TextureColoring) {
 		self.bind_program();
@@ -180,16 +451,71 @@ impl OpenGl {
 				TextureColoring::MixTexture => 1,
 				TextureColoring::Texture => 0,
 				TextureColoring::Color => 2,
+				TextureColoring::Gradient => 3,
 			};
 
 			self.gl.uniform_1_i32(uniform.as_ref(), ival);
 		}
 	}
 
+	/// Set the gradient for both rectangles and sdfs. `points` is where a
+	/// linear gradient goes from and to, or the center and radius of a radial
+	/// one. Corners gradients take their colors from the first four stops.
+	/// Stops past [MAX_GRADIENT_STOPS] are ignored.
+	pub fn set_gradient(&self, kind: GradientKind, points: [f32; 4], stops: &[(f32, Color)]) {
+		let stops = match stops {
+			[] => &[(0.0, Color::TRANSPARENT)],
+			stops => &stops[..stops.len().min(MAX_GRADIENT_STOPS)],
+		};
+
+		let offsets: Vec<f32> = stops.iter().map(|(offset, _)| *offset).collect();
+		let colors: Vec<f32> = stops
+			.iter()
+			.flat_map(|(_, c)| [c.r, c.g, c.b, c.a])
+			.collect();
+		let kind = match kind {
+			GradientKind::Linear => 0,
+			GradientKind::Radial => 1,
+			GradientKind::Corners => 2,
+		};
+
+		unsafe {
+			for program in [self.program, self.sdf] {
+				self.bind_user_program(program);
+				let uniform = |name| self.gl.get_uniform_location(program, name);
+
+				self.gl
+					.uniform_1_i32(uniform("GradientKind").as_ref(), kind);
+				self.gl
+					.uniform_4_f32_slice(uniform("GradientPoints").as_ref(), &points);
+				self.gl
+					.uniform_4_f32_slice(uniform("GradientColors").as_ref(), &colors);
+				self.gl
+					.uniform_1_f32_slice(uniform("GradientOffsets").as_ref(), &offsets);
+				self.gl
+					.uniform_1_i32(uniform("GradientStops").as_ref(), stops.len() as i32);
+			}
+		}
+	}
+
+	/// Fill sdfs with the gradient instead of their color
+	pub fn set_sdf_gradient(&self, gradient: bool) {
+		self.bind_sdf();
+		unsafe {
+			let uniform = self.gl.get_uniform_location(self.sdf, "Gradient");
+			self.gl.uniform_1_i32(uniform.as_ref(), gradient as i32);
+		}
+	}
+
 	pub fn draw_rectangle(&self, pos: Vec2, dim: Vec2) {
 		self.gen_draw_rectangle(pos, dim, &self.draw_rect);
 	}
 
+	/// Draw a rectangle at `pos` which is `dim` large, both in OpenGL coordinates
+	pub fn draw_rectangle_raw_coords(&self, pos: Vec2, dim: Vec2) {
+		self.gen_draw_rectangle_raw_coords(pos, dim, &self.draw_rect);
+	}
+
 	//FIXME: wtf, lol
 	pub(crate) fn gen_draw_rectangle(&self, pos: Vec2, dim: Vec2, rect: &Rectangle) {
 		// The rectangle we use to draw, self.draw_rect, spans from (OpenGL Normalized Coordinates)
@@ -215,6 +541,56 @@ impl OpenGl {
 		}
 	}
 
+	/// Draw triangles with the texture program, every three of `vertices` in
+	/// murs making one
+	pub fn draw_triangles(&self, vertices: &[Vec2]) {
+		if vertices.is_empty() {
+			return;
+		}
+
+		let vertices: Vec<Vec2> = vertices
+			.iter()
+			.map(|v| self.transform.vec_to_opengl(*v))
+			.collect();
+
+		unsafe {
+			self.bind_program();
+
+			let uniform_position = self.gl.get_uniform_location(self.program, "WorldPosition");
+			let uniform_scale = self.gl.get_uniform_location(self.program, "Scale");
+			self.gl.uniform_2_f32(uniform_position.as_ref(), 0.0, 0.0);
+			self.gl.uniform_2_f32(uniform_scale.as_ref(), 1.0, 1.0);
+
+			self.mesh.draw(&self.gl, &vertices);
+		}
+	}
+
+	/// Draw a rectangle at `pos` murs with a user program. The program gets the
+	/// same `WorldPosition` and `Scale` uniforms as our texture program.
+	pub fn draw_rectangle_user_program(&self, program: Program, pos: Vec2, dim: Vec2) {
+		let gl_pos = self.transform.vec_to_opengl(pos);
+		let gl_dim = self.transform.vec_to_opengl(dim / 2);
+
+		unsafe {
+			self.bind_user_program(program);
+
+			let uniform_position = self.gl.get_uniform_location(program, "WorldPosition");
+			let uniform_scale = self.gl.get_uniform_location(program, "Scale");
+			let uniform_tex_offset = self.gl.get_uniform_location(program, "TextureOffset");
+			let uniform_tex_scale = self.gl.get_uniform_location(program, "TextureScale");
+			self.gl
+				.uniform_2_f32(uniform_position.as_ref(), gl_pos.x, gl_pos.y);
+			self.gl
+				.uniform_2_f32(uniform_scale.as_ref(), gl_dim.x, gl_dim.y);
+			self.gl.uniform_2_f32(uniform_tex_offset.as_ref(), 0.0, 0.0);
+			self.gl.uniform_2_f32(uniform_tex_scale.as_ref(), 1.0, 1.0);
+
+			self.draw_rect.bind(&self.gl);
+			self.gl
+				.draw_elements(glow::TRIANGLES, 6, glow::UNSIGNED_BYTE, 0);
+		}
+	}
+
 	//FIXME: god damnit
 	pub(crate) fn gen_draw_rectangle_raw_coords(&self, pos: Vec2, dim: Vec2, rect: &Rectangle) {
 		// The rectangle we use to draw, self.draw_rect, spans from (OpenGL Normalized Coordinates)
@@ -240,87 +616,206 @@ impl OpenGl {
 		}
 	}
 
-	pub fn draw_sdf(&self, sdf: SignedDistance) {
+	/// Set up the distance field text program for the next glyphs
+	pub fn set_distance_text_style(&self, color: Color, style: DistanceTextStyle) {
+		let program = self.sdf_text;
+		self.bind_user_program(program);
+
+		unsafe {
+			let uniform = |name| self.gl.get_uniform_location(program, name);
+			let set_color = |name, c: Color| {
+				self.gl
+					.uniform_4_f32(uniform(name).as_ref(), c.r, c.g, c.b, c.a)
+			};
+
+			set_color("Color", color);
+			set_color("OutlineColor", style.outline);
+			set_color("GlowColor", style.glow);
+			self.gl
+				.uniform_1_f32(uniform("OutlineWidth").as_ref(), style.outline_width);
+			self.gl
+				.uniform_1_f32(uniform("GlowWidth").as_ref(), style.glow_width);
+			self.gl
+				.uniform_1_f32(uniform("Softness").as_ref(), style.softness);
+		}
+	}
+
+	/// Draw a glyph from a distance field atlas. `pos` and `dim` are in OpenGL
+	/// coordinates and the texture ones are the glyph's place in the atlas.
+	pub fn draw_distance_glyph(
+		&self,
+		pos: Vec2,
+		dim: Vec2,
+		texture_position: Vec2,
+		texture_dimensions: Vec2,
+	) {
+		let program = self.sdf_text;
+		let gl_dim = dim / 2;
+
+		unsafe {
+			self.bind_user_program(program);
+
+			let uniform = |name| self.gl.get_uniform_location(program, name);
+			self.gl
+				.uniform_2_f32(uniform("WorldPosition").as_ref(), pos.x, pos.y);
+			self.gl
+				.uniform_2_f32(uniform("Scale").as_ref(), gl_dim.x, gl_dim.y);
+			self.gl.uniform_2_f32(
+				uniform("TextureOffset").as_ref(),
+				texture_position.x,
+				texture_position.y,
+			);
+			self.gl.uniform_2_f32(
+				uniform("TextureScale").as_ref(),
+				texture_dimensions.x,
+				texture_dimensions.y,
+			);
+
+			self.draw_rect.bind(&self.gl);
+			self.gl
+				.draw_elements(glow::TRIANGLES, 6, glow::UNSIGNED_BYTE, 0);
+		}
+	}
+
+	pub fn draw_sdf(&self, sdf: SignedDistance, style: &SdfStyle) {
 		self.bind_sdf();
 
-		match sdf {
-			SignedDistance::Circle {
-				center,
-				radius,
-				color,
-			} => unsafe {
-				let uniform_color = self.gl.get_uniform_location(self.sdf, "Color");
-				let uniform_pointpair = self.gl.get_uniform_location(self.sdf, "PointPair");
-				let uniform_drawmethod = self.gl.get_uniform_location(self.sdf, "DrawMethod");
+		let color = sdf.color();
+		let (point_pair, parameters) = sdf.uniforms(&self.transform);
 
-				let pixel_center = self.transform.vec_to_pixels(center);
+		unsafe {
+			let uniform_color = self.gl.get_uniform_location(self.sdf, "Color");
+			let uniform_pointpair = self.gl.get_uniform_location(self.sdf, "PointPair");
+			let uniform_parameters = self.gl.get_uniform_location(self.sdf, "Parameters");
+			let uniform_drawmethod = self.gl.get_uniform_location(self.sdf, "DrawMethod");
 
-				self.gl
-					.uniform_4_f32(uniform_color.as_ref(), color.r, color.g, color.b, color.a);
-				self.gl.uniform_4_f32(
-					uniform_pointpair.as_ref(),
-					pixel_center.x,
-					pixel_center.y,
-					radius as f32,
-					0.0,
-				);
-				self.gl
-					.uniform_1_i32(uniform_drawmethod.as_ref(), sdf.draw_method_index());
-			},
-			SignedDistance::LineSegment {
-				start,
-				end,
-				thickness,
-				color,
-			} => unsafe {
-				let uniform_color = self.gl.get_uniform_location(self.sdf, "Color");
-				let uniform_pointpair = self.gl.get_uniform_location(self.sdf, "PointPair");
-				let uniform_parameters = self.gl.get_uniform_location(self.sdf, "Parameters");
-				let uniform_drawmethod = self.gl.get_uniform_location(self.sdf, "DrawMethod");
+			self.gl
+				.uniform_4_f32(uniform_color.as_ref(), color.r, color.g, color.b, color.a);
+			self.gl
+				.uniform_4_f32_slice(uniform_pointpair.as_ref(), &point_pair);
+			self.gl
+				.uniform_4_f32_slice(uniform_parameters.as_ref(), &parameters);
+			self.gl
+				.uniform_1_i32(uniform_drawmethod.as_ref(), sdf.draw_method_index());
+		}
 
-				let pixel_start = self.transform.vec_to_pixels(start);
-				let pixel_end = self.transform.vec_to_pixels(end);
+		let (pos, dim) = sdf.get_bounds();
+		self.draw_sdf_bounds(pos, dim, style);
+	}
 
-				self.gl
-					.uniform_4_f32(uniform_color.as_ref(), color.r, color.g, color.b, color.a);
-				self.gl.uniform_4_f32(
-					uniform_pointpair.as_ref(),
-					pixel_start.x,
-					pixel_start.y,
-					pixel_end.x,
-					pixel_end.y,
-				);
-				self.gl
-					.uniform_4_f32(uniform_parameters.as_ref(), thickness as f32, 0.0, 0.0, 0.0);
-				self.gl
-					.uniform_1_i32(uniform_drawmethod.as_ref(), sdf.draw_method_index());
-			},
-			SignedDistance::Octogon {
-				center,
-				radius,
-				color,
-			} => unsafe {
-				let uniform_color = self.gl.get_uniform_location(self.sdf, "Color");
-				let uniform_pointpair = self.gl.get_uniform_location(self.sdf, "PointPair");
-				let uniform_drawmethod = self.gl.get_uniform_location(self.sdf, "DrawMethod");
+	/// Draw a lot of shapes in one draw call. They can't have a stroke or a
+	/// shadow.
+	pub fn draw_sdf_batch(&self, sdfs: &[SignedDistance]) {
+		if sdfs.is_empty() {
+			return;
+		}
 
-				let pixel_center = self.transform.vec_to_pixels(center);
+		self.bind_user_program(self.sdf_batch);
+
+		// Room to fade the edge over
+		let margin = 1.0 / self.transform.mur_size as f32;
+		let mut instances = Vec::with_capacity(sdfs.len() * INSTANCE_FLOATS);
+		for sdf in sdfs {
+			let (pos, dim) = sdf.get_bounds();
+			let gl_pos = self.transform.vec_to_opengl(pos);
+			let gl_dim = self
+				.transform
+				.vec_to_opengl(dim / 2 + Vec2::new(margin, margin));
+			let (point_pair, parameters) = sdf.uniforms(&self.transform);
+			let color = sdf.color();
+
+			instances.extend_from_slice(&[gl_pos.x, gl_pos.y, gl_dim.x, gl_dim.y]);
+			instances.extend_from_slice(&point_pair);
+			instances.extend_from_slice(&parameters);
+			instances.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+			instances.push(sdf.draw_method_index() as f32);
+		}
 
+		unsafe { self.sdf_instances.draw(&self.gl, &instances) }
+	}
+
+	/// Draw shapes put together with an [SdfShape], all in one color
+	pub fn draw_sdf_shape(
+		&self,
+		shape: &SdfShape,
+		color: Color,
+		style: &SdfStyle,
+	) -> Result<(), SdfShapeError> {
+		let (program, length) = shape.program(&self.transform)?;
+		let (min, max) = match shape.get_bounds() {
+			Some(bounds) => bounds,
+			None => return Ok(()),
+		};
+
+		self.bind_sdf();
+
+		unsafe {
+			let uniform = |name| self.gl.get_uniform_location(self.sdf, name);
+
+			self.gl.uniform_4_f32(
+				uniform("Color").as_ref(),
+				color.r,
+				color.g,
+				color.b,
+				color.a,
+			);
+			self.gl.uniform_1_i32(uniform("DrawMethod").as_ref(), 10);
+			self.gl
+				.uniform_4_f32_slice(uniform("Shape").as_ref(), &program);
+			self.gl
+				.uniform_1_i32(uniform("ShapeLength").as_ref(), length);
+		}
+
+		self.draw_sdf_bounds((min + max) / 2, max - min, style);
+		Ok(())
+	}
+
+	/// Set the style uniforms and draw a quad over the shape, centered on `pos`
+	/// and `dim` big, in murs.
+	fn draw_sdf_bounds(&self, pos: Vec2, dim: Vec2, style: &SdfStyle) {
+		let shadow = style.shadow.unwrap_or_default();
+		let mur_size = self.transform.mur_size as f32;
+		let shadow_offset = shadow.offset * mur_size;
+		let corner = self.transform.vec_to_pixels(pos - dim / 2);
+		let size = dim * mur_size;
+
+		unsafe {
+			let uniform = |name| self.gl.get_uniform_location(self.sdf, name);
+			self.gl.uniform_4_f32(
+				uniform("GradientBounds").as_ref(),
+				corner.x,
+				corner.y,
+				size.x,
+				size.y,
+			);
+
+			let set_color = |name, c: Color| {
 				self.gl
-					.uniform_4_f32(uniform_color.as_ref(), color.r, color.g, color.b, color.a);
-				self.gl.uniform_4_f32(
-					uniform_pointpair.as_ref(),
-					pixel_center.x,
-					pixel_center.y,
-					radius as f32,
-					0.0,
-				);
-				self.gl
-					.uniform_1_i32(uniform_drawmethod.as_ref(), sdf.draw_method_index());
-			},
+					.uniform_4_f32(uniform(name).as_ref(), c.r, c.g, c.b, c.a)
+			};
+
+			set_color("StrokeColor", style.stroke_color);
+			set_color("ShadowColor", shadow.color);
+			self.gl.uniform_1_f32(
+				uniform("StrokeWidth").as_ref(),
+				style.stroke_width * mur_size,
+			);
+			self.gl.uniform_2_f32(
+				uniform("ShadowOffset").as_ref(),
+				shadow_offset.x,
+				shadow_offset.y,
+			);
+			self.gl.uniform_1_f32(
+				uniform("ShadowSoftness").as_ref(),
+				shadow.softness * mur_size,
+			);
 		}
 
-		let (pos, dim) = sdf.get_bounds(&self.transform);
+		// Grow the bounds so there's room for the stroke, the shadow, and a
+		// pixel to fade the edge over.
+		let margin = style.stroke_width / 2.0 + shadow.softness + 1.0 / mur_size;
+		let margin = Vec2::new(margin, margin) + shadow.offset.abs();
+		let dim = dim + margin * 2;
 		let gl_pos = self.transform.vec_to_opengl(pos);
 		let gl_dim = self.transform.vec_to_opengl(dim / 2);
 
@@ -338,19 +833,46 @@ impl OpenGl {
 		}
 	}
 
-	pub fn draw_fullscreen(&self) {
+	/// Cover the whole viewport with `program`. If `flipped` is set the texture
+	/// coordinates are turned upside down, which is what you want when sampling
+	/// something we rendered into a framebuffer.
+	pub fn draw_fullscreen(&self, program: Program, flipped: bool) {
+		let (offset, scale) = if flipped {
+			(Vec2::new(0.0, 1.0), Vec2::new(1.0, -1.0))
+		} else {
+			(Vec2::ZERO, Vec2::new(1.0, 1.0))
+		};
+
 		unsafe {
-			self.bind_program();
+			self.bind_user_program(program);
 
-			let uniform_position = self.gl.get_uniform_location(self.program, "WorldPosition");
-			let uniform_scale = self.gl.get_uniform_location(self.program, "Scale");
+			let uniform_position = self.gl.get_uniform_location(program, "WorldPosition");
+			let uniform_scale = self.gl.get_uniform_location(program, "Scale");
+			let uniform_tex_offset = self.gl.get_uniform_location(program, "TextureOffset");
+			let uniform_tex_scale = self.gl.get_uniform_location(program, "TextureScale");
 			self.gl.uniform_2_f32(uniform_position.as_ref(), 0.0, 0.0);
 			self.gl.uniform_2_f32(uniform_scale.as_ref(), 1.0, 1.0);
+			self.gl
+				.uniform_2_f32(uniform_tex_offset.as_ref(), offset.x, offset.y);
+			self.gl
+				.uniform_2_f32(uniform_tex_scale.as_ref(), scale.x, scale.y);
 
 			self.draw_rect.bind(&self.gl);
 			self.gl
 				.draw_elements(glow::TRIANGLES, 6, glow::UNSIGNED_BYTE, 0);
 		}
+
+		// If this was our program we just clobbered the texture transform
+		if program == self.program {
+			self.texture_transform.set((offset, scale));
+		}
+	}
+
+	/// Cover the whole viewport with the bound texture as it is. This leaves
+	/// the texture coloring set to [TextureColoring::Texture].
+	pub fn draw_texture_fullscreen(&self, flipped: bool) {
+		self.set_texture_coloring_uniform(TextureColoring::Texture);
+		self.draw_fullscreen(self.program, flipped);
 	}
 }
 
@@ -359,96 +881,369 @@ impl Drop for OpenGl {
 		unsafe {
 			self.gl.delete_program(self.program);
 			self.gl.delete_program(self.sdf);
+			self.gl.delete_program(self.sdf_batch);
+			self.gl.delete_program(self.sdf_text);
 			self.draw_rect.delete(&self.gl);
+			self.mesh.delete(&self.gl);
+			self.sdf_instances.delete(&self.gl);
+		}
+	}
+}
+
+/// How what we draw is combined with what's already there
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum BlendMode {
+	/// Regular transparency. The default.
+	#[default]
+	Alpha,
+	/// Transparency for colors that have already been multiplied by their
+	/// alpha, like textures made with [crate::Smitten::make_texture_premultiplied]
+	Premultiplied,
+	/// Adds to the color underneath. Good for light and glows.
+	Add,
+	/// Multiplies the color underneath. Good for shadows and tinting.
+	Multiply,
+	/// The inverse of multiply; brightens the color underneath.
+	Screen,
+	/// Ignores what's underneath entirely, alpha included.
+	Replace,
+}
+
+impl BlendMode {
+	/// The source and destination factors for glBlendFunc
+	fn factors(&self) -> (u32, u32) {
+		match self {
+			BlendMode::Alpha => (glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA),
+			BlendMode::Premultiplied => (glow::ONE, glow::ONE_MINUS_SRC_ALPHA),
+			BlendMode::Add => (glow::SRC_ALPHA, glow::ONE),
+			BlendMode::Multiply => (glow::DST_COLOR, glow::ZERO),
+			BlendMode::Screen => (glow::ONE, glow::ONE_MINUS_SRC_COLOR),
+			BlendMode::Replace => (glow::ONE, glow::ZERO),
 		}
 	}
 }
 
+/// Effects drawn around distance field glyphs. Widths are in the atlas'
+/// distance units, where `0.5` is as far out as the field goes.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct DistanceTextStyle {
+	pub outline: Color,
+	pub outline_width: f32,
+	pub glow: Color,
+	pub glow_width: f32,
+	pub softness: f32,
+}
+
+/// Most stops a gradient can have. Must match the arrays in the shaders.
+pub const MAX_GRADIENT_STOPS: usize = 8;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum GradientKind {
+	Linear,
+	Radial,
+	Corners,
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum TextureColoring {
 	MixTexture,
 	Texture,
 	Color,
+	/// Whatever was last given to [OpenGl::set_gradient]
+	Gradient,
 }
 
+/// Shapes drawn by their signed distance function. Everything is in murs.
 #[derive(Copy, Clone, Debug)]
 pub enum SignedDistance {
 	Circle {
 		center: Vec2,
-		radius: u32,
+		radius: f32,
 		color: Color,
 	},
 	LineSegment {
 		start: Vec2,
 		end: Vec2,
-		thickness: u32,
+		thickness: f32,
 		color: Color,
 	},
 	Octogon {
 		center: Vec2,
-		radius: u32,
+		radius: f32,
+		color: Color,
+	},
+	RoundedRect {
+		center: Vec2,
+		/// Width and height in murs
+		size: Vec2,
+		/// Radius of the corners. It can't be more than half the shortest side.
+		radius: f32,
+		color: Color,
+	},
+	Ring {
+		center: Vec2,
+		/// Radius of the middle of the ring
+		radius: f32,
+		thickness: f32,
+		color: Color,
+	},
+	/// Part of a ring with rounded ends, going counter-clockwise from `start`
+	/// to `end`. Angles are in radians from the right.
+	Arc {
+		center: Vec2,
+		radius: f32,
+		thickness: f32,
+		start: f32,
+		end: f32,
+		color: Color,
+	},
+	Triangle {
+		a: Vec2,
+		b: Vec2,
+		c: Vec2,
+		color: Color,
+	},
+	Ellipse {
+		center: Vec2,
+		/// Horizontal and vertical radius in murs
+		radii: Vec2,
+		color: Color,
+	},
+	/// A regular polygon with its first corner pointing up
+	Polygon {
+		center: Vec2,
+		/// Distance from the center to the corners
+		radius: f32,
+		sides: u32,
 		color: Color,
 	},
 }
 
+/// How a [SignedDistance] is drawn besides its color
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SdfStyle {
+	pub stroke_color: Color,
+	/// Centered on the edge of the shape. Zero has no stroke.
+	pub stroke_width: f32,
+	pub shadow: Option<SdfShadow>,
+}
+
+impl SdfStyle {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn stroke<C: Into<Color>>(mut self, color: C, width: f32) -> Self {
+		self.stroke_color = color.into();
+		self.stroke_width = width;
+		self
+	}
+
+	/// A blurred copy of the shape underneath it, moved by `offset`
+	pub fn shadow<C, V>(mut self, color: C, offset: V, softness: f32) -> Self
+	where
+		C: Into<Color>,
+		V: Into<Vec2>,
+	{
+		self.shadow = Some(SdfShadow {
+			color: color.into(),
+			offset: offset.into(),
+			softness,
+		});
+		self
+	}
+
+	/// A shadow that isn't moved, so it glows out from every side
+	pub fn glow<C: Into<Color>>(self, color: C, radius: f32) -> Self {
+		self.shadow(color, Vec2::ZERO, radius)
+	}
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SdfShadow {
+	pub color: Color,
+	pub offset: Vec2,
+	/// How far the shadow fades out over
+	pub softness: f32,
+}
+
 impl SignedDistance {
 	fn draw_method_index(&self) -> i32 {
 		match self {
 			SignedDistance::Circle { .. } => 1,
 			SignedDistance::LineSegment { .. } => 2,
 			SignedDistance::Octogon { .. } => 3,
+			SignedDistance::RoundedRect { .. } => 4,
+			SignedDistance::Ring { .. } => 5,
+			SignedDistance::Arc { .. } => 6,
+			SignedDistance::Triangle { .. } => 7,
+			SignedDistance::Ellipse { .. } => 8,
+			SignedDistance::Polygon { .. } => 9,
 		}
 	}
 
-	fn get_bounds(&self, trns: &Transform) -> (Vec2, Vec2) {
+	fn color(&self) -> Color {
 		match self {
-			SignedDistance::Circle { center, radius, .. } => {
-				(*center, Vec2::new(*radius as f32, *radius as f32) * 2)
+			SignedDistance::Circle { color, .. }
+			| SignedDistance::LineSegment { color, .. }
+			| SignedDistance::Octogon { color, .. }
+			| SignedDistance::RoundedRect { color, .. }
+			| SignedDistance::Ring { color, .. }
+			| SignedDistance::Arc { color, .. }
+			| SignedDistance::Triangle { color, .. }
+			| SignedDistance::Ellipse { color, .. }
+			| SignedDistance::Polygon { color, .. } => *color,
+		}
+	}
+
+	/// The same shape in another color
+	pub fn with_color<C: Into<Color>>(mut self, new: C) -> Self {
+		match &mut self {
+			SignedDistance::Circle { color, .. }
+			| SignedDistance::LineSegment { color, .. }
+			| SignedDistance::Octogon { color, .. }
+			| SignedDistance::RoundedRect { color, .. }
+			| SignedDistance::Ring { color, .. }
+			| SignedDistance::Arc { color, .. }
+			| SignedDistance::Triangle { color, .. }
+			| SignedDistance::Ellipse { color, .. }
+			| SignedDistance::Polygon { color, .. } => *color = new.into(),
+		}
+
+		self
+	}
+
+	/// The `PointPair` and `Parameters` uniforms of the sdf program, in
+	/// window pixels.
+	fn uniforms(&self, trns: &Transform) -> ([f32; 4], [f32; 4]) {
+		let px = |v: Vec2| trns.vec_to_pixels(v);
+		let len = |murs: f32| murs * trns.mur_size as f32;
+
+		match *self {
+			SignedDistance::Circle { center, radius, .. }
+			| SignedDistance::Octogon { center, radius, .. } => {
+				let c = px(center);
+				([c.x, c.y, len(radius), 0.0], [0.0; 4])
 			}
 			SignedDistance::LineSegment {
 				start,
 				end,
+				thickness: radius,
+				..
+			} => {
+				let (s, e) = (px(start), px(end));
+				([s.x, s.y, e.x, e.y], [len(radius), 0.0, 0.0, 0.0])
+			}
+			SignedDistance::RoundedRect {
+				center,
+				size,
+				radius,
+				..
+			} => {
+				let c = px(center);
+				(
+					[c.x, c.y, len(size.x) / 2.0, len(size.y) / 2.0],
+					[len(radius), 0.0, 0.0, 0.0],
+				)
+			}
+			SignedDistance::Ring {
+				center,
+				radius,
 				thickness,
 				..
 			} => {
-				let mut rstart = Vec2::ZERO;
-				let mut rend = Vec2::ZERO;
-
-				if start.x < end.x {
-					rstart.x = start.x;
-					rend.x = end.x;
-				} else {
-					rstart.x = end.x;
-					rend.x = start.x;
-				}
-
-				if start.y < end.y {
-					rstart.y = start.y;
-					rend.y = end.y;
-				} else {
-					rstart.y = end.y;
-					rend.y = start.y;
-				}
-
-				let dim = rend - rstart;
-				let hdim = dim.abs() / 2;
+				let c = px(center);
+				(
+					[c.x, c.y, len(radius), 0.0],
+					[len(thickness), 0.0, 0.0, 0.0],
+				)
+			}
+			SignedDistance::Arc {
+				center,
+				radius,
+				thickness,
+				start,
+				end,
+				..
+			} => {
+				let c = px(center);
+				// Always go counter-clockwise, even across zero, but keep a
+				// whole turn whole
+				let sweep = match (end - start).rem_euclid(TAU) {
+					sweep if sweep == 0.0 && end != start => TAU,
+					sweep => sweep,
+				};
 
 				(
-					rstart + hdim,
-					hdim * 2
-						+ Vec2::new(
-							trns.pixels_to_mur(*thickness),
-							trns.pixels_to_mur(*thickness),
-						) * 3,
+					[c.x, c.y, len(radius), 0.0],
+					[len(thickness), start, start + sweep, 0.0],
 				)
 			}
-			SignedDistance::Octogon { center, radius, .. } => {
-				(*center, Vec2::new(*radius as f32, *radius as f32) * 2)
+			SignedDistance::Triangle { a, b, c, .. } => {
+				let (a, b, c) = (px(a), px(b), px(c));
+				([a.x, a.y, b.x, b.y], [c.x, c.y, 0.0, 0.0])
+			}
+			SignedDistance::Ellipse { center, radii, .. } => {
+				let c = px(center);
+				([c.x, c.y, len(radii.x), len(radii.y)], [0.0; 4])
+			}
+			SignedDistance::Polygon {
+				center,
+				radius,
+				sides,
+				..
+			} => {
+				let c = px(center);
+				(
+					[c.x, c.y, len(radius), 0.0],
+					[sides.max(3) as f32, 0.0, 0.0, 0.0],
+				)
 			}
 		}
 	}
 
-	pub fn line_segment<S, E, C>(start: S, end: E, thickness: u32, color: C) -> SignedDistance
+	/// The center and size of a rectangle the shape fits in
+	fn get_bounds(&self) -> (Vec2, Vec2) {
+		let square = |side: f32| Vec2::new(side, side);
+
+		match *self {
+			SignedDistance::Circle { center, radius, .. }
+			| SignedDistance::Octogon { center, radius, .. }
+			| SignedDistance::Polygon { center, radius, .. } => (center, square(radius * 2.0)),
+			SignedDistance::LineSegment {
+				start,
+				end,
+				thickness: radius,
+				..
+			} => {
+				let min = Vec2::new(start.x.min(end.x), start.y.min(end.y));
+				let max = Vec2::new(start.x.max(end.x), start.y.max(end.y));
+				((min + max) / 2, max - min + square(radius * 2.0))
+			}
+			SignedDistance::RoundedRect { center, size, .. } => (center, size),
+			// Arcs are never bigger than the ring they're a part of
+			SignedDistance::Ring {
+				center,
+				radius,
+				thickness,
+				..
+			}
+			| SignedDistance::Arc {
+				center,
+				radius,
+				thickness,
+				..
+			} => (center, square(radius * 2.0 + thickness)),
+			SignedDistance::Triangle { a, b, c, .. } => {
+				let min = Vec2::new(a.x.min(b.x).min(c.x), a.y.min(b.y).min(c.y));
+				let max = Vec2::new(a.x.max(b.x).max(c.x), a.y.max(b.y).max(c.y));
+				((min + max) / 2, max - min)
+			}
+			SignedDistance::Ellipse { center, radii, .. } => (center, radii * 2),
+		}
+	}
+
+	pub fn line_segment<S, E, C>(start: S, end: E, thickness: f32, color: C) -> SignedDistance
 	where
 		S: Into<Vec2>,
 		E: Into<Vec2>,
@@ -462,7 +1257,7 @@ impl SignedDistance {
 		}
 	}
 
-	pub fn circle<P, C>(center: P, radius: u32, color: C) -> SignedDistance
+	pub fn circle<P, C>(center: P, radius: f32, color: C) -> SignedDistance
 	where
 		P: Into<Vec2>,
 		C: Into<Color>,
@@ -474,7 +1269,7 @@ impl SignedDistance {
 		}
 	}
 
-	pub fn octogon<P, C>(center: P, radius: u32, color: C) -> SignedDistance
+	pub fn octogon<P, C>(center: P, radius: f32, color: C) -> SignedDistance
 	where
 		P: Into<Vec2>,
 		C: Into<Color>,
@@ -485,4 +1280,110 @@ impl SignedDistance {
 			color: color.into(),
 		}
 	}
+
+	pub fn rounded_rect<P, S, C>(center: P, size: S, radius: f32, color: C) -> SignedDistance
+	where
+		P: Into<Vec2>,
+		S: Into<Vec2>,
+		C: Into<Color>,
+	{
+		SignedDistance::RoundedRect {
+			center: center.into(),
+			size: size.into(),
+			radius,
+			color: color.into(),
+		}
+	}
+
+	pub fn ring<P, C>(center: P, radius: f32, thickness: f32, color: C) -> SignedDistance
+	where
+		P: Into<Vec2>,
+		C: Into<Color>,
+	{
+		SignedDistance::Ring {
+			center: center.into(),
+			radius,
+			thickness,
+			color: color.into(),
+		}
+	}
+
+	pub fn arc<P, C>(
+		center: P,
+		radius: f32,
+		thickness: f32,
+		start: f32,
+		end: f32,
+		color: C,
+	) -> SignedDistance
+	where
+		P: Into<Vec2>,
+		C: Into<Color>,
+	{
+		SignedDistance::Arc {
+			center: center.into(),
+			radius,
+			thickness,
+			start,
+			end,
+			color: color.into(),
+		}
+	}
+
+	pub fn triangle<A, B, D, C>(a: A, b: B, c: D, color: C) -> SignedDistance
+	where
+		A: Into<Vec2>,
+		B: Into<Vec2>,
+		D: Into<Vec2>,
+		C: Into<Color>,
+	{
+		SignedDistance::Triangle {
+			a: a.into(),
+			b: b.into(),
+			c: c.into(),
+			color: color.into(),
+		}
+	}
+
+	pub fn ellipse<P, R, C>(center: P, radii: R, color: C) -> SignedDistance
+	where
+		P: Into<Vec2>,
+		R: Into<Vec2>,
+		C: Into<Color>,
+	{
+		SignedDistance::Ellipse {
+			center: center.into(),
+			radii: radii.into(),
+			color: color.into(),
+		}
+	}
+
+	/// A line with round ends `radius` out from it. It's the same as a line
+	/// segment, whose thickness is measured from the middle too.
+	pub fn capsule<S, E, C>(start: S, end: E, radius: f32, color: C) -> SignedDistance
+	where
+		S: Into<Vec2>,
+		E: Into<Vec2>,
+		C: Into<Color>,
+	{
+		SignedDistance::LineSegment {
+			start: start.into(),
+			end: end.into(),
+			thickness: radius,
+			color: color.into(),
+		}
+	}
+
+	pub fn polygon<P, C>(center: P, radius: f32, sides: u32, color: C) -> SignedDistance
+	where
+		P: Into<Vec2>,
+		C: Into<Color>,
+	{
+		SignedDistance::Polygon {
+			center: center.into(),
+			radius,
+			sides,
+			color: color.into(),
+		}
+	}
 }
@@ -0,0 +1,73 @@
+use glow::{Buffer, HasContext, VertexArray};
+
+/// Floats per shape in the instance buffer: the bounds, point pair,
+/// parameters, and color as four each, and then the draw method.
+pub const INSTANCE_FLOATS: usize = 17;
+
+/// A quad and a buffer of shapes to draw on it, so a whole slice of
+/// [super::SignedDistance] goes out in one instanced draw call.
+pub struct SdfBatch {
+	vao: VertexArray,
+	quad: Buffer,
+	instances: Buffer,
+}
+
+impl SdfBatch {
+	pub fn new(gl: &glow::Context) -> Self {
+		// A triangle strip around the quad
+		let corners = [-1.0f32, -1.0, 1.0, -1.0, -1.0, 1.0, 1.0, 1.0];
+		let corner_buffer: Vec<u8> = corners.iter().flat_map(|f| f.to_le_bytes()).collect();
+
+		unsafe {
+			let vao = gl.create_vertex_array().unwrap();
+			gl.bind_vertex_array(Some(vao));
+
+			let quad = gl.create_buffer().unwrap();
+			gl.bind_buffer(glow::ARRAY_BUFFER, Some(quad));
+			gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, &corner_buffer, glow::STATIC_DRAW);
+			gl.vertex_attrib_pointer_f32(0, 2, glow::FLOAT, false, 2 * 4, 0);
+			gl.enable_vertex_attrib_array(0);
+
+			let instances = gl.create_buffer().unwrap();
+			gl.bind_buffer(glow::ARRAY_BUFFER, Some(instances));
+
+			let stride = INSTANCE_FLOATS as i32 * 4;
+			for (location, size) in [(1, 4), (2, 4), (3, 4), (4, 4), (5, 1)] {
+				let offset = (location as i32 - 1) * 4 * 4;
+				gl.vertex_attrib_pointer_f32(location, size, glow::FLOAT, false, stride, offset);
+				gl.vertex_attrib_divisor(location, 1);
+				gl.enable_vertex_attrib_array(location);
+			}
+
+			gl.bind_buffer(glow::ARRAY_BUFFER, None);
+			gl.bind_vertex_array(None);
+
+			Self {
+				vao,
+				quad,
+				instances,
+			}
+		}
+	}
+
+	/// Draw `instances`, which is [INSTANCE_FLOATS] floats for every shape.
+	/// The sdf batch program has to be bound.
+	pub unsafe fn draw(&self, gl: &glow::Context, instances: &[f32]) {
+		let count = instances.len() / INSTANCE_FLOATS;
+		let buffer: Vec<u8> = instances.iter().flat_map(|f| f.to_le_bytes()).collect();
+
+		gl.bind_vertex_array(Some(self.vao));
+		gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.instances));
+		gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, &buffer, glow::STREAM_DRAW);
+		gl.bind_buffer(glow::ARRAY_BUFFER, None);
+
+		gl.draw_arrays_instanced(glow::TRIANGLE_STRIP, 0, 4, count as i32);
+		gl.bind_vertex_array(None);
+	}
+
+	pub unsafe fn delete(&self, gl: &glow::Context) {
+		gl.delete_vertex_array(self.vao);
+		gl.delete_buffer(self.quad);
+		gl.delete_buffer(self.instances);
+	}
+}
@@ -0,0 +1,301 @@
+use thiserror::Error;
+
+use crate::Vec2;
+
+use super::{SignedDistance, Transform};
+
+/// The most instructions a shape can flatten to. Every shape and every
+/// combination of two shapes is one instruction. Must match the size of the
+/// `Shape` uniform in sdf.frag.
+const MAX_SHAPE_INSTRUCTIONS: usize = 24;
+/// How many distances can be waiting to be combined at once. Must match
+/// the stack in sdf.frag.
+const MAX_SHAPE_DEPTH: usize = 8;
+/// vec4s per instruction in the `Shape` uniform of sdf.frag
+const INSTRUCTION_VEC4S: usize = 5;
+
+/// Signed distance shapes put together into one shape, drawn in a single pass
+/// with [crate::Smitten::sdf_shape]. The colors of the shapes inside are
+/// ignored; the whole thing is drawn in one color.
+///
+/// ```ignore
+/// let shape = SdfShape::from(SignedDistance::circle((0.0, 0.0), 1.0, Color::WHITE))
+///     .smooth_union(SignedDistance::circle((1.0, 0.0), 0.5, Color::WHITE), 0.25)
+///     .rotate(0.5)
+///     .translate((2.0, 0.0));
+/// ```
+#[derive(Clone, Debug)]
+pub enum SdfShape {
+	Shape(SignedDistance),
+	Union(Box<SdfShape>, Box<SdfShape>),
+	/// The first shape with the second cut out of it
+	Subtract(Box<SdfShape>, Box<SdfShape>),
+	Intersect(Box<SdfShape>, Box<SdfShape>),
+	/// A union that blends the shapes together where they're within `k` murs
+	/// of each other
+	SmoothUnion {
+		a: Box<SdfShape>,
+		b: Box<SdfShape>,
+		k: f32,
+	},
+	Translate(Box<SdfShape>, Vec2),
+	/// Rotate counter-clockwise around the origin, in radians
+	Rotate(Box<SdfShape>, f32),
+	/// Scale away from the origin
+	Scale(Box<SdfShape>, f32),
+}
+
+impl SdfShape {
+	pub fn union<S: Into<SdfShape>>(self, other: S) -> Self {
+		SdfShape::Union(Box::new(self), Box::new(other.into()))
+	}
+
+	pub fn subtract<S: Into<SdfShape>>(self, other: S) -> Self {
+		SdfShape::Subtract(Box::new(self), Box::new(other.into()))
+	}
+
+	pub fn intersect<S: Into<SdfShape>>(self, other: S) -> Self {
+		SdfShape::Intersect(Box::new(self), Box::new(other.into()))
+	}
+
+	pub fn smooth_union<S: Into<SdfShape>>(self, other: S, k: f32) -> Self {
+		SdfShape::SmoothUnion {
+			a: Box::new(self),
+			b: Box::new(other.into()),
+			k,
+		}
+	}
+
+	pub fn translate<V: Into<Vec2>>(self, offset: V) -> Self {
+		SdfShape::Translate(Box::new(self), offset.into())
+	}
+
+	pub fn rotate(self, radians: f32) -> Self {
+		SdfShape::Rotate(Box::new(self), radians)
+	}
+
+	pub fn scale(self, scale: f32) -> Self {
+		SdfShape::Scale(Box::new(self), scale)
+	}
+
+	/// Whether the shape is small enough to be drawn. At most 24 shapes and
+	/// combinations fit, and no more than 8 distances can be waiting to be
+	/// combined, which happens when the right side of a combination is itself
+	/// combined shapes. Chains like `a.union(b).union(c)` never wait on more
+	/// than two.
+	pub fn check(&self) -> Result<(), SdfShapeError> {
+		let (instructions, depth) = self.size();
+
+		if instructions > MAX_SHAPE_INSTRUCTIONS {
+			Err(SdfShapeError::TooManyShapes(
+				instructions,
+				MAX_SHAPE_INSTRUCTIONS,
+			))
+		} else if depth > MAX_SHAPE_DEPTH {
+			Err(SdfShapeError::TooDeep(depth, MAX_SHAPE_DEPTH))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// How many instructions the shape flattens to and how deep the stack
+	/// running them gets
+	fn size(&self) -> (usize, usize) {
+		let combine = |a: &SdfShape, b: &SdfShape| {
+			let ((left, left_depth), (right, right_depth)) = (a.size(), b.size());
+			(left + right + 1, left_depth.max(right_depth + 1))
+		};
+
+		match self {
+			SdfShape::Shape(_) => (1, 1),
+			SdfShape::Union(a, b) | SdfShape::Subtract(a, b) | SdfShape::Intersect(a, b) => {
+				combine(a, b)
+			}
+			SdfShape::SmoothUnion { a, b, .. } => combine(a, b),
+			SdfShape::Translate(shape, _) | SdfShape::Rotate(shape, _) => shape.size(),
+			SdfShape::Scale(shape, _) => shape.size(),
+		}
+	}
+
+	/// The `Shape` uniform of sdf.frag and how many instructions are in it
+	pub(crate) fn program(&self, trns: &Transform) -> Result<(Vec<f32>, i32), SdfShapeError> {
+		self.check()?;
+
+		let mut program = vec![];
+		self.flatten(trns, Inverse::IDENTITY, &mut program);
+		let length = program.len() / (INSTRUCTION_VEC4S * 4);
+
+		Ok((program, length as i32))
+	}
+
+	/// Write the shape in postfix, so the shader can run it with a stack
+	fn flatten(&self, trns: &Transform, inverse: Inverse, program: &mut Vec<f32>) {
+		let mut combine = |op: f32, k: f32, a: &SdfShape, b: &SdfShape| {
+			a.flatten(trns, inverse, program);
+			b.flatten(trns, inverse, program);
+
+			// The blend grows and shrinks with the shapes it's between
+			let k = k * inverse.scale * trns.mur_size as f32;
+			program.extend_from_slice(&[op, 0.0, k, 0.0]);
+			program.extend_from_slice(&[0.0; (INSTRUCTION_VEC4S - 1) * 4]);
+		};
+
+		match self {
+			SdfShape::Shape(sdf) => {
+				let (point_pair, parameters) = sdf.uniforms(trns);
+				let (matrix, offset) = inverse.to_pixels(trns);
+
+				program.extend_from_slice(&[
+					0.0,
+					sdf.draw_method_index() as f32,
+					0.0,
+					inverse.scale,
+				]);
+				program.extend_from_slice(&point_pair);
+				program.extend_from_slice(&parameters);
+				program.extend_from_slice(&matrix);
+				program.extend_from_slice(&[offset.x, offset.y, 0.0, 0.0]);
+			}
+			SdfShape::Union(a, b) => combine(1.0, 0.0, a, b),
+			SdfShape::Subtract(a, b) => combine(2.0, 0.0, a, b),
+			SdfShape::Intersect(a, b) => combine(3.0, 0.0, a, b),
+			SdfShape::SmoothUnion { a, b, k } => combine(4.0, *k, a, b),
+			SdfShape::Translate(shape, offset) => {
+				shape.flatten(trns, inverse.translate(*offset), program)
+			}
+			SdfShape::Rotate(shape, radians) => {
+				shape.flatten(trns, inverse.rotate(*radians), program)
+			}
+			SdfShape::Scale(shape, scale) => shape.flatten(trns, inverse.scale(*scale), program),
+		}
+	}
+
+	/// The smallest and largest corner of a rectangle the shape fits in, in
+	/// murs. `None` if nothing is left of it.
+	pub(crate) fn get_bounds(&self) -> Option<(Vec2, Vec2)> {
+		let union = |a: Option<(Vec2, Vec2)>, b: Option<(Vec2, Vec2)>| match (a, b) {
+			(Some((amin, amax)), Some((bmin, bmax))) => Some((
+				Vec2::new(amin.x.min(bmin.x), amin.y.min(bmin.y)),
+				Vec2::new(amax.x.max(bmax.x), amax.y.max(bmax.y)),
+			)),
+			(a, None) => a,
+			(None, b) => b,
+		};
+		let corners = |bounds: Option<(Vec2, Vec2)>, f: &dyn Fn(Vec2) -> Vec2| {
+			let (min, max) = bounds?;
+			[min, max, Vec2::new(min.x, max.y), Vec2::new(max.x, min.y)]
+				.into_iter()
+				.map(|corner| Some((f(corner), f(corner))))
+				.fold(None, union)
+		};
+
+		match self {
+			SdfShape::Shape(sdf) => {
+				let (center, dim) = sdf.get_bounds();
+				Some((center - dim / 2, center + dim / 2))
+			}
+			SdfShape::Union(a, b) => union(a.get_bounds(), b.get_bounds()),
+			// The blend can bulge out a little past either shape
+			SdfShape::SmoothUnion { a, b, k } => {
+				let (min, max) = union(a.get_bounds(), b.get_bounds())?;
+				let k = Vec2::new(*k, *k);
+				Some((min - k, max + k))
+			}
+			SdfShape::Subtract(a, _) => a.get_bounds(),
+			SdfShape::Intersect(a, b) => {
+				let ((amin, amax), (bmin, bmax)) = (a.get_bounds()?, b.get_bounds()?);
+				let min = Vec2::new(amin.x.max(bmin.x), amin.y.max(bmin.y));
+				let max = Vec2::new(amax.x.min(bmax.x), amax.y.min(bmax.y));
+
+				(min.x < max.x && min.y < max.y).then_some((min, max))
+			}
+			SdfShape::Translate(shape, offset) => corners(shape.get_bounds(), &|v| v + *offset),
+			SdfShape::Rotate(shape, radians) => {
+				corners(shape.get_bounds(), &|v| rotate(v, *radians))
+			}
+			SdfShape::Scale(shape, scale) => corners(shape.get_bounds(), &|v| v * *scale),
+		}
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum SdfShapeError {
+	#[error("The shape is made of {0} shapes and combinations but only {1} fit")]
+	TooManyShapes(usize, usize),
+	#[error("The shape needs {0} distances kept at once but only {1} fit")]
+	TooDeep(usize, usize),
+}
+
+impl From<SignedDistance> for SdfShape {
+	fn from(sdf: SignedDistance) -> Self {
+		SdfShape::Shape(sdf)
+	}
+}
+
+fn rotate(v: Vec2, radians: f32) -> Vec2 {
+	let (sin, cos) = radians.sin_cos();
+	Vec2::new(cos * v.x - sin * v.y, sin * v.x + cos * v.y)
+}
+
+/// Takes a point from where the shape is drawn back to where it was defined,
+/// in murs. `matrix` is row major.
+#[derive(Copy, Clone, Debug)]
+struct Inverse {
+	matrix: [f32; 4],
+	offset: Vec2,
+	/// How much bigger distances are where the shape is drawn
+	scale: f32,
+}
+
+impl Inverse {
+	const IDENTITY: Inverse = Inverse {
+		matrix: [1.0, 0.0, 0.0, 1.0],
+		offset: Vec2::ZERO,
+		scale: 1.0,
+	};
+
+	fn apply(&self, v: Vec2) -> Vec2 {
+		let [a, b, c, d] = self.matrix;
+		Vec2::new(a * v.x + b * v.y, c * v.x + d * v.y)
+	}
+
+	/// Also undo a transform inside the ones we already undo, given by its
+	/// inverse
+	fn then(self, matrix: [f32; 4], offset: Vec2, scale: f32) -> Self {
+		let [a, b, c, d] = matrix;
+		let [e, f, g, h] = self.matrix;
+		let inner = Inverse {
+			matrix,
+			offset: Vec2::ZERO,
+			scale: 1.0,
+		};
+
+		Inverse {
+			matrix: [a * e + b * g, a * f + b * h, c * e + d * g, c * f + d * h],
+			offset: inner.apply(self.offset) + offset,
+			scale: self.scale * scale,
+		}
+	}
+
+	fn translate(self, offset: Vec2) -> Self {
+		self.then([1.0, 0.0, 0.0, 1.0], Vec2::ZERO - offset, 1.0)
+	}
+
+	fn rotate(self, radians: f32) -> Self {
+		let (sin, cos) = (-radians).sin_cos();
+		self.then([cos, -sin, sin, cos], Vec2::ZERO, 1.0)
+	}
+
+	fn scale(self, scale: f32) -> Self {
+		let inv = 1.0 / scale;
+		self.then([inv, 0.0, 0.0, inv], Vec2::ZERO, scale)
+	}
+
+	/// The same thing, but from window pixels to window pixels
+	fn to_pixels(self, trns: &Transform) -> ([f32; 4], Vec2) {
+		let center = trns.screen_vec / 2;
+		let offset = center - self.apply(center) + self.offset * trns.mur_size;
+
+		(self.matrix, offset)
+	}
+}
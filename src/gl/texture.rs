@@ -7,6 +7,11 @@ use super::OpenGl;
 
 pub struct Texture {
 	texture: NativeTexture,
+	/// Textures we render into with a framebuffer have their origin in the
+	/// bottom left, so they need to be drawn upside down.
+	pub flipped: bool,
+	pub width: usize,
+	pub height: usize,
 }
 
 impl Texture {
@@ -25,6 +30,26 @@ impl Texture {
 		)
 	}
 
+	/// Load a texture and multiply the color channels by alpha before
+	/// uploading it. Draw these with [super::BlendMode::Premultiplied].
+	pub fn from_file_premultiplied<P: AsRef<Path>>(ogl: &OpenGl, path: P) -> Self {
+		let img = ImageReader::open(path)
+			.unwrap()
+			.decode()
+			.unwrap()
+			.to_rgba8();
+
+		let mut buffer = img.to_vec();
+		premultiply(&mut buffer);
+
+		Self::rgba8(
+			ogl,
+			img.width() as usize,
+			img.height() as usize,
+			buffer.as_slice(),
+		)
+	}
+
 	pub fn rgba8(ogl: &OpenGl, width: usize, height: usize, buffer: &[u8]) -> Self {
 		let gl = ogl.gl();
 		let texture = unsafe {
@@ -46,7 +71,118 @@ impl Texture {
 			tex
 		};
 
-		Self { texture }
+		Self {
+			texture,
+			flipped: false,
+			width,
+			height,
+		}
+	}
+
+	/// An uninitialized texture meant to be rendered into
+	pub fn empty(ogl: &OpenGl, width: usize, height: usize) -> Self {
+		let gl = ogl.gl();
+		let texture = unsafe {
+			let tex = gl.create_texture().unwrap();
+			gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+			gl.tex_image_2d(
+				glow::TEXTURE_2D,
+				0,
+				glow::RGBA as i32,
+				width as i32,
+				height as i32,
+				0,
+				glow::RGBA,
+				glow::UNSIGNED_BYTE,
+				None,
+			);
+
+			// There's no mipmaps here, so we can't use the default minification filter
+			gl.tex_parameter_i32(
+				glow::TEXTURE_2D,
+				glow::TEXTURE_MIN_FILTER,
+				glow::NEAREST as i32,
+			);
+			gl.tex_parameter_i32(
+				glow::TEXTURE_2D,
+				glow::TEXTURE_MAG_FILTER,
+				glow::NEAREST as i32,
+			);
+
+			tex
+		};
+
+		Self {
+			texture,
+			flipped: true,
+			width,
+			height,
+		}
+	}
+
+	/// A transparent texture with no mipmaps, meant to have things packed into
+	/// it with [Texture::update]
+	pub fn atlas(ogl: &OpenGl, width: usize, height: usize, filter: Filter) -> Self {
+		let gl = ogl.gl();
+		let texture = unsafe {
+			let tex = gl.create_texture().unwrap();
+			gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+			gl.tex_image_2d(
+				glow::TEXTURE_2D,
+				0,
+				glow::RGBA as i32,
+				width as i32,
+				height as i32,
+				0,
+				glow::RGBA,
+				glow::UNSIGNED_BYTE,
+				Some(&vec![0; width * height * 4]),
+			);
+
+			gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, filter.gl());
+			gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, filter.gl());
+
+			tex
+		};
+
+		Self {
+			texture,
+			flipped: false,
+			width,
+			height,
+		}
+	}
+
+	/// Replace a region of the texture with an RGBA8 buffer. This binds the
+	/// texture.
+	pub fn update(
+		&self,
+		ogl: &OpenGl,
+		x: usize,
+		y: usize,
+		width: usize,
+		height: usize,
+		buffer: &[u8],
+	) {
+		let gl = ogl.gl();
+		unsafe {
+			gl.bind_texture(glow::TEXTURE_2D, Some(self.texture));
+			gl.tex_sub_image_2d(
+				glow::TEXTURE_2D,
+				0,
+				x as i32,
+				y as i32,
+				width as i32,
+				height as i32,
+				glow::RGBA,
+				glow::UNSIGNED_BYTE,
+				glow::PixelUnpackData::Slice(buffer),
+			);
+		}
+	}
+
+	pub fn native(&self) -> NativeTexture {
+		self.texture
 	}
 
 	pub unsafe fn bind(&self, ogl: &OpenGl) {
@@ -57,3 +193,33 @@ impl Texture {
 		ogl.gl().delete_texture(self.texture)
 	}
 }
+
+/// Multiply the color channels of an RGBA8 buffer by its alpha
+pub fn premultiply(buffer: &mut [u8]) {
+	for pixel in buffer.chunks_exact_mut(4) {
+		let alpha = pixel[3] as u16;
+
+		for channel in &mut pixel[..3] {
+			*channel = ((*channel as u16 * alpha + 127) / 255) as u8;
+		}
+	}
+}
+
+/// How a texture is sampled when it's drawn bigger or smaller than it is
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Filter {
+	/// Blend between pixels. Smooth, but blurry up close.
+	#[default]
+	Linear,
+	/// Take the closest pixel. Good for pixel art.
+	Nearest,
+}
+
+impl Filter {
+	fn gl(&self) -> i32 {
+		match self {
+			Filter::Linear => glow::LINEAR as i32,
+			Filter::Nearest => glow::NEAREST as i32,
+		}
+	}
+}
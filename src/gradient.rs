@@ -0,0 +1,127 @@
+use crate::{gl::MAX_GRADIENT_STOPS, Color, Vec2};
+
+/// Colors blended across a rectangle or signed distance shape. Positions go
+/// from (0, 0) at the bottom left of what's being drawn to (1, 1) at the top
+/// right.
+///
+/// ```ignore
+/// let sky = Gradient::vertical(Color::rgb(0.9, 0.5, 0.2), Color::rgb(0.2, 0.3, 0.8));
+/// let sun = Gradient::radial((0.5, 0.5), 0.5, [(0.0, Color::YELLOW), (1.0, Color::WHITE)]);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Gradient {
+	Linear {
+		from: Vec2,
+		to: Vec2,
+		stops: GradientStops,
+	},
+	Radial {
+		center: Vec2,
+		radius: f32,
+		stops: GradientStops,
+	},
+	/// A color for each corner, blended between
+	Corners {
+		top_left: Color,
+		top_right: Color,
+		bottom_left: Color,
+		bottom_right: Color,
+	},
+}
+
+impl Gradient {
+	pub fn linear<F, T, S>(from: F, to: T, stops: S) -> Self
+	where
+		F: Into<Vec2>,
+		T: Into<Vec2>,
+		S: Into<GradientStops>,
+	{
+		Gradient::Linear {
+			from: from.into(),
+			to: to.into(),
+			stops: stops.into(),
+		}
+	}
+
+	pub fn radial<C: Into<Vec2>, S: Into<GradientStops>>(center: C, radius: f32, stops: S) -> Self {
+		Gradient::Radial {
+			center: center.into(),
+			radius,
+			stops: stops.into(),
+		}
+	}
+
+	/// From `from` at the bottom to `to` at the top
+	pub fn vertical<F: Into<Color>, T: Into<Color>>(from: F, to: T) -> Self {
+		Self::linear(
+			(0.0, 0.0),
+			(0.0, 1.0),
+			[(0.0, from.into()), (1.0, to.into())],
+		)
+	}
+
+	/// From `from` on the left to `to` on the right
+	pub fn horizontal<F: Into<Color>, T: Into<Color>>(from: F, to: T) -> Self {
+		Self::linear(
+			(0.0, 0.0),
+			(1.0, 0.0),
+			[(0.0, from.into()), (1.0, to.into())],
+		)
+	}
+}
+
+/// The colors along a gradient, as an offset and the color there. They're
+/// sorted by offset so they can be given in any order. There can be up to
+/// eight stops and any past that are dropped.
+/// A gradient with no stops is see-through.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GradientStops {
+	stops: [(f32, Color); MAX_GRADIENT_STOPS],
+	len: usize,
+}
+
+impl GradientStops {
+	pub fn new(stops: &[(f32, Color)]) -> Self {
+		let len = stops.len().min(MAX_GRADIENT_STOPS);
+		let mut fixed = [(0.0, Color::TRANSPARENT); MAX_GRADIENT_STOPS];
+		fixed[..len].copy_from_slice(&stops[..len]);
+		fixed[..len].sort_by(|a, b| a.0.total_cmp(&b.0));
+
+		Self { stops: fixed, len }
+	}
+
+	pub fn as_slice(&self) -> &[(f32, Color)] {
+		&self.stops[..self.len]
+	}
+}
+
+impl From<&[(f32, Color)]> for GradientStops {
+	fn from(stops: &[(f32, Color)]) -> Self {
+		Self::new(stops)
+	}
+}
+
+impl<const N: usize> From<[(f32, Color); N]> for GradientStops {
+	fn from(stops: [(f32, Color); N]) -> Self {
+		Self::new(&stops)
+	}
+}
+
+/// What to fill a signed distance shape with
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SdfFill {
+	Color(Color),
+	Gradient(Gradient),
+}
+
+impl From<Color> for SdfFill {
+	fn from(color: Color) -> Self {
+		SdfFill::Color(color)
+	}
+}
+
+impl From<Gradient> for SdfFill {
+	fn from(gradient: Gradient) -> Self {
+		SdfFill::Gradient(gradient)
+	}
+}
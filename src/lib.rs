@@ -1,18 +1,32 @@
 #![feature(const_fn_floating_point_arithmetic)]
+mod bitmapfont;
 mod color;
+mod debug;
 mod gl;
+mod gradient;
+mod nineslice;
+mod path;
+mod richtext;
+mod shader;
 mod smittenfont;
+mod text;
 mod vec2;
 
-use smittenfont::SmittenFont;
+use bitmapfont::BitmapFont;
+use shader::Shader;
+use smittenfont::{SmittenFont, SDF_SPREAD};
 
 use std::{
-	cell::Cell,
+	cell::{Cell, RefCell},
 	collections::{HashMap, HashSet},
-	path::Path,
+	path::Path as FilePath,
 };
 
-use gl::{OpenGl, Texture, TextureColoring, Transform};
+#[cfg(feature = "debug-draw")]
+use debug::DebugShape;
+use gl::{
+	DistanceTextStyle, Framebuffer, GradientKind, OpenGl, Texture, TextureColoring, Transform,
+};
 use glutin::{
 	dpi::PhysicalSize,
 	event::{ElementState, Event, VirtualKeyCode, WindowEvent},
@@ -25,9 +39,21 @@ use glutin::{
 #[cfg(target_os = "linux")]
 use glutin::platform::unix::WindowBuilderExtUnix;
 
+pub use bitmapfont::BitmapFontError;
 pub use color::Color;
-pub use gl::SignedDistance;
+pub use debug::DebugDraw;
+pub use gl::{BlendMode, Filter, SdfShadow, SdfShape, SdfShapeError, SdfStyle, SignedDistance};
 pub use glutin::event::MouseButton;
+pub use gradient::{Gradient, GradientStops, SdfFill};
+pub use nineslice::{NineSlice, SliceFill};
+pub use path::{FillRule, LineCap, LineJoin, Path, StrokeStyle};
+pub use richtext::{RichText, RichTextError};
+pub use shader::Uniform;
+pub use smittenfont::{FontError, FontKind, FontOptions};
+pub use text::{
+	GlyphDraw, GlyphMetrics, LaidOutText, SpanStyle, TextAlign, TextEffects, TextLayout,
+	TextMetrics, TextShadow,
+};
 pub use vec2::Vec2;
 
 pub type PixelSize = PhysicalSize<u32>;
@@ -38,6 +64,29 @@ pub struct TextureId(u32);
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct FontId(u32);
 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct RenderTargetId(u32);
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ShaderId(u32);
+
+struct RenderTarget {
+	framebuffer: Framebuffer,
+	texture: TextureId,
+}
+
+/// How wide lines from [Smitten::debug] are, in pixels
+#[cfg(feature = "debug-draw")]
+const DEBUG_LINE_WIDTH: f32 = 2.0;
+/// How long the heads of debug arrows are, in pixels
+#[cfg(feature = "debug-draw")]
+const DEBUG_ARROW_HEAD: f32 = 10.0;
+/// How tall debug labels are, in pixels
+#[cfg(feature = "debug-draw")]
+const DEBUG_TEXT_SIZE: f32 = 16.0;
+
+type LayeredDraw = (f32, Box<dyn FnOnce(&Smitten)>);
+
 struct InputState {
 	down_keys: HashSet<Key>,
 	down_scancode: HashSet<u32>,
@@ -77,7 +126,8 @@ pub struct Smitten {
 	event_loop: EventLoop<()>,
 
 	gl: OpenGl,
-	current_color: Cell<Color>,
+	/// The color uniform, if we know what it is
+	current_color: Cell<Option<Color>>,
 	current_texture: Cell<Option<TextureId>>,
 	texture_coloring: TextureColoring,
 
@@ -87,6 +137,23 @@ pub struct Smitten {
 	next_fontid: FontId,
 	fonts: HashMap<FontId, SmittenFont>,
 
+	next_render_targetid: RenderTargetId,
+	render_targets: HashMap<RenderTargetId, RenderTarget>,
+	current_render_target: Option<RenderTargetId>,
+
+	/// Scissor rectangles in pixels, the last is the intersection of them all
+	clip_stack: RefCell<Vec<(i32, i32, i32, i32)>>,
+	mask_depth: Cell<u8>,
+
+	/// Draws waiting to be sorted by their layer, see [Smitten::layer]
+	layered: RefCell<Vec<LayeredDraw>>,
+	debug: DebugDraw,
+
+	next_shaderid: ShaderId,
+	shaders: HashMap<ShaderId, Shader>,
+	/// Intermediate targets that post-processing passes ping-pong between
+	post_targets: Option<[RenderTargetId; 2]>,
+
 	input_state: InputState,
 }
 
@@ -114,6 +181,7 @@ impl Smitten {
 
 		let wc = ContextBuilder::new()
 			.with_vsync(true)
+			.with_stencil_buffer(8)
 			.build_windowed(wb, &el)
 			.unwrap();
 
@@ -128,13 +196,23 @@ impl Smitten {
 			context,
 			event_loop: el,
 			gl,
-			current_color: Cell::new(Color::rgb(0.0, 0.0, 0.0)),
+			current_color: Cell::new(None),
 			current_texture: Cell::new(None),
 			texture_coloring: TextureColoring::Texture,
 			next_textureid: TextureId(0),
 			textures: HashMap::new(),
 			next_fontid: FontId(0),
 			fonts: HashMap::new(),
+			next_render_targetid: RenderTargetId(0),
+			render_targets: HashMap::new(),
+			current_render_target: None,
+			clip_stack: RefCell::new(vec![]),
+			mask_depth: Cell::new(0),
+			layered: RefCell::new(vec![]),
+			debug: DebugDraw::default(),
+			next_shaderid: ShaderId(0),
+			shaders: HashMap::new(),
+			post_targets: None,
 			input_state: InputState::new(),
 		}
 	}
@@ -155,6 +233,11 @@ impl Smitten {
 			}
 		}
 
+		#[cfg(debug_assertions)]
+		for shader in self.shaders.values_mut() {
+			shader.reload(&self.gl);
+		}
+
 		events
 	}
 
@@ -163,13 +246,164 @@ impl Smitten {
 	}
 
 	pub fn swap(&self) {
+		self.flush_layers();
+		#[cfg(feature = "debug-draw")]
+		self.flush_debug();
 		self.context.swap_buffers().unwrap()
 	}
 
+	/// Lines, shapes, and labels drawn on top of everything else this frame.
+	/// See [DebugDraw].
+	pub fn debug(&self) -> &DebugDraw {
+		&self.debug
+	}
+
+	/// Draw everything given to [Smitten::debug] and forget it
+	#[cfg(feature = "debug-draw")]
+	fn flush_debug(&self) {
+		let shapes = self.debug.take();
+		if shapes.is_empty() {
+			return;
+		}
+
+		// Debug drawing isn't clipped or masked
+		self.gl.set_scissor(None);
+		self.gl.apply_mask(0);
+		let previous = self.gl.blend_mode();
+		self.gl.set_blend_mode(BlendMode::Alpha);
+
+		let mur_size = self.gl.transform.mur_size as f32;
+		let width = DEBUG_LINE_WIDTH / mur_size;
+		let line = |start, end, color| SignedDistance::line_segment(start, end, width / 2.0, color);
+
+		let mut lines = vec![];
+		for shape in &shapes {
+			match *shape {
+				DebugShape::Line { start, end, color } => lines.push(line(start, end, color)),
+				DebugShape::Rect {
+					center,
+					size,
+					color,
+				} => {
+					let half = size / 2;
+					let corners = [
+						center + Vec2::new(-half.x, -half.y),
+						center + Vec2::new(half.x, -half.y),
+						center + Vec2::new(half.x, half.y),
+						center + Vec2::new(-half.x, half.y),
+					];
+
+					for index in 0..4 {
+						lines.push(line(corners[index], corners[(index + 1) % 4], color));
+					}
+				}
+				DebugShape::Circle {
+					center,
+					radius,
+					color,
+				} => lines.push(SignedDistance::ring(center, radius, width, color)),
+				DebugShape::Arrow { start, end, color } => {
+					lines.push(line(start, end, color));
+
+					let direction = (end - start).normalize_correct();
+					let length = DEBUG_ARROW_HEAD / mur_size;
+					let back = end - direction * length;
+					let side = Vec2::new(-direction.y, direction.x) * (length / 2.0);
+
+					if direction != Vec2::ZERO {
+						lines.push(line(end, back + side, color));
+						lines.push(line(end, back - side, color));
+					}
+				}
+				DebugShape::Text { .. } => (),
+			}
+		}
+		self.sdf_batch(&lines);
+
+		// Labels go over the shapes
+		let font = self.debug.label_font();
+		if let Some((font, sfont)) = font.and_then(|id| Some((id, self.fonts.get(&id)?))) {
+			let scale = DEBUG_TEXT_SIZE / sfont.pixel_size(1.0);
+
+			for shape in shapes {
+				if let DebugShape::Text {
+					position,
+					text,
+					color,
+				} = shape
+				{
+					// Text is drawn centered, so move it to have its top left
+					// corner at the position
+					let laid = self.layout_text(&TextLayout::new(font, text, scale));
+					let bounds = laid.bounds();
+					let center = position + Vec2::new(bounds.x, -bounds.y) / 2.0;
+					self.draw_text(&laid, center, color);
+				}
+			}
+		}
+
+		self.gl.set_blend_mode(previous);
+		self.gl
+			.set_scissor(self.clip_stack.borrow().last().copied());
+		self.gl.apply_mask(self.mask_depth.get());
+	}
+
+	/// Queue the drawing in `f` on layer `z` instead of drawing it right away.
+	/// Layers are drawn lowest `z` first when the frame is swapped, or when
+	/// a [Smitten::with_render_target] ends, so what's on top no longer
+	/// depends on the order things were submitted in. Draws on the same layer
+	/// keep their order.
+	///
+	/// Everything that isn't on a layer is drawn before all the layers. State
+	/// like clips and blend modes is whatever it is when the layer is drawn, so
+	/// set it inside of `f`.
+	pub fn layer<F>(&self, z: f32, f: F)
+	where
+		F: FnOnce(&Smitten) + 'static,
+	{
+		self.layered.borrow_mut().push((z, Box::new(f)));
+	}
+
+	/// Draw all the queued layers now
+	pub fn flush_layers(&self) {
+		loop {
+			let mut layered = self.layered.take();
+			if layered.is_empty() {
+				break;
+			}
+
+			// sort_by is stable, so draws on the same layer stay in order
+			layered.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+			for (_, draw) in layered {
+				draw(self);
+			}
+		}
+	}
+
 	pub fn clear_color<C: Into<Color>>(&mut self, color: C) {
 		self.gl.clear_color(color)
 	}
 
+	/// Set how everything drawn from now on blends with what's underneath
+	pub fn blend_mode(&self, mode: BlendMode) {
+		self.gl.set_blend_mode(mode)
+	}
+
+	/// Draw everything in `f` with a blend mode, going back to the current one
+	/// afterwards.
+	pub fn with_blend_mode<F>(&self, mode: BlendMode, f: F)
+	where
+		F: FnOnce(&Smitten),
+	{
+		let previous = self.gl.blend_mode();
+		self.gl.set_blend_mode(mode);
+
+		f(self);
+
+		self.gl.set_blend_mode(previous);
+	}
+
 	pub fn texture_coloring(&mut self, flag: bool) {
 		let value = if flag {
 			TextureColoring::MixTexture
@@ -230,7 +464,7 @@ impl Smitten {
 		}
 	}
 
-	pub fn make_texture<P: AsRef<Path>>(&mut self, path: P) -> TextureId {
+	pub fn make_texture<P: AsRef<FilePath>>(&mut self, path: P) -> TextureId {
 		let tex = Texture::from_file(&self.gl, path);
 		let id = self.next_textureid;
 
@@ -240,6 +474,19 @@ impl Smitten {
 		id
 	}
 
+	/// Load a texture with its colors multiplied by alpha. These should be
+	/// drawn with [BlendMode::Premultiplied], which keeps the edges of soft
+	/// things like glows and particles from going dark.
+	pub fn make_texture_premultiplied<P: AsRef<FilePath>>(&mut self, path: P) -> TextureId {
+		let tex = Texture::from_file_premultiplied(&self.gl, path);
+		let id = self.next_textureid;
+
+		self.textures.insert(id, tex);
+		self.next_textureid.0 += 1;
+
+		id
+	}
+
 	pub fn make_texture_rgba8(&mut self, width: usize, height: usize, buffer: &[u8]) -> TextureId {
 		let tex = Texture::rgba8(&self.gl, width, height, buffer);
 		let id = self.next_textureid;
@@ -250,9 +497,311 @@ impl Smitten {
 		id
 	}
 
-	pub fn make_font<P: AsRef<Path>>(&mut self, path: P) -> FontId {
-		self.gl.bind_program();
-		let font = SmittenFont::from_file(&self.gl, path);
+	/// How big a texture is, in pixels
+	pub fn texture_size(&self, tid: TextureId) -> PixelSize {
+		let texture = self.texture(tid);
+		PixelSize::new(texture.width as u32, texture.height as u32)
+	}
+
+	/// Make an offscreen render target that is `size` pixels large. Draw into
+	/// it with [Smitten::with_render_target] and draw the result with the
+	/// texture from [Smitten::render_target_texture].
+	pub fn create_render_target<P: Into<PixelSize>>(&mut self, size: P) -> RenderTargetId {
+		let (framebuffer, tex) = Framebuffer::new(&self.gl, size.into());
+		let texture = self.next_textureid;
+
+		self.textures.insert(texture, tex);
+		self.next_textureid.0 += 1;
+
+		// Creating the texture bound it, so whatever we think is bound is wrong now
+		self.current_texture.set(None);
+		// and making the framebuffer left the window bound, even if we're
+		// inside of a with_render_target
+		self.bind_render_target(self.current_render_target);
+
+		let id = self.next_render_targetid;
+		self.render_targets.insert(
+			id,
+			RenderTarget {
+				framebuffer,
+				texture,
+			},
+		);
+		self.next_render_targetid.0 += 1;
+
+		id
+	}
+
+	/// The texture that a render target draws into
+	pub fn render_target_texture(&self, rtid: RenderTargetId) -> TextureId {
+		self.render_targets.get(&rtid).unwrap().texture
+	}
+
+	/// Redirect all drawing done in `f` into the render target. The murs
+	/// inside are the same size as the window's, but are centered on the
+	/// render target.
+	pub fn with_render_target<F>(&mut self, rtid: RenderTargetId, f: F)
+	where
+		F: FnOnce(&mut Smitten),
+	{
+		let size = self.render_targets.get(&rtid).unwrap().framebuffer.size;
+		self.bind_render_target(Some(rtid));
+
+		let transform = Transform::new(size, self.gl.transform.mur_size);
+		let previous_transform = std::mem::replace(&mut self.gl.transform, transform);
+		let previous_target = self.current_render_target.replace(rtid);
+
+		// Clipping and layers belong to whatever we were drawing to before
+		let previous_clips = self.clip_stack.take();
+		let previous_masks = self.mask_depth.replace(0);
+		let previous_layers = self.layered.take();
+		self.gl.set_scissor(None);
+		self.gl.apply_mask(0);
+
+		f(self);
+		self.flush_layers();
+		self.layered.replace(previous_layers);
+
+		self.gl.transform = previous_transform;
+		self.current_render_target = previous_target;
+		self.bind_render_target(previous_target);
+
+		self.gl.set_scissor(previous_clips.last().copied());
+		self.gl.apply_mask(previous_masks);
+		self.clip_stack.replace(previous_clips);
+		self.mask_depth.set(previous_masks);
+	}
+
+	fn delete_render_target(&mut self, rtid: RenderTargetId) {
+		if let Some(target) = self.render_targets.remove(&rtid) {
+			unsafe { target.framebuffer.delete(&self.gl) };
+
+			if let Some(tex) = self.textures.remove(&target.texture) {
+				unsafe { tex.delete(&self.gl) };
+			}
+
+			if self.current_texture.get() == Some(target.texture) {
+				self.current_texture.set(None);
+			}
+		}
+	}
+
+	fn bind_render_target(&self, rtid: Option<RenderTargetId>) {
+		match rtid {
+			Some(rtid) => {
+				let target = self.render_targets.get(&rtid).unwrap();
+				self.gl
+					.bind_framebuffer(Some(&target.framebuffer), target.framebuffer.size);
+			}
+			None => self
+				.gl
+				.bind_framebuffer(None, self.context.window().inner_size()),
+		}
+	}
+
+	/// Load a vertex and fragment shader from disk. In debug builds the shader
+	/// is recompiled whenever either file changes.
+	///
+	/// The vertex shader gets the model position at location 0, the texture
+	/// coordinate at location 1, and the uniforms `WorldPosition`, `Scale`,
+	/// `TextureOffset` and `TextureScale`. See `shaders/texture.vert`.
+	pub fn load_shader<V, F>(&mut self, vertex: V, fragment: F) -> ShaderId
+	where
+		V: AsRef<FilePath>,
+		F: AsRef<FilePath>,
+	{
+		let shader = Shader::from_files(&self.gl, vertex, fragment);
+		self.insert_shader(shader)
+	}
+
+	/// Load a fragment shader from disk and pair it with our own vertex shader,
+	/// which passes along `TexCoord`. Otherwise the same as [Smitten::load_shader].
+	pub fn load_fragment_shader<F: AsRef<FilePath>>(&mut self, fragment: F) -> ShaderId {
+		let shader = Shader::from_fragment_file(&self.gl, fragment);
+		self.insert_shader(shader)
+	}
+
+	fn insert_shader(&mut self, shader: Shader) -> ShaderId {
+		let id = self.next_shaderid;
+
+		self.shaders.insert(id, shader);
+		self.next_shaderid.0 += 1;
+
+		id
+	}
+
+	/// Set a uniform on a shader. It's kept until it's set again and is applied
+	/// every time the shader is used.
+	pub fn set_uniform<N, U>(&mut self, shader: ShaderId, name: N, value: U)
+	where
+		N: Into<String>,
+		U: Into<Uniform>,
+	{
+		self.shaders
+			.get_mut(&shader)
+			.unwrap()
+			.set_uniform(name.into(), value.into());
+	}
+
+	/// Run the contents of a render target through a chain of full screen
+	/// shaders, drawing the result of the last one to whatever we're currently
+	/// drawing to. Each pass gets the output of the one before it as the
+	/// `Texture` uniform. With no passes the render target is drawn as it is.
+	pub fn post_process(&mut self, source: RenderTargetId, passes: &[ShaderId]) {
+		if passes.is_empty() {
+			let texture = self.render_target_texture(source);
+			self.bind_texture(texture);
+			self.gl.draw_texture_fullscreen(true);
+			self.gl.set_texture_coloring_uniform(self.texture_coloring);
+			return;
+		}
+
+		let size = self.render_targets.get(&source).unwrap().framebuffer.size;
+		let targets = self.post_targets(size);
+
+		let mut input = self.render_target_texture(source);
+		for (idx, pass) in passes.iter().enumerate() {
+			let output = if idx == passes.len() - 1 {
+				self.bind_render_target(self.current_render_target);
+				None
+			} else {
+				let target = targets[idx % 2];
+				self.bind_render_target(Some(target));
+				self.gl.clear_transparent();
+				Some(target)
+			};
+
+			let shader = self.shaders.get_mut(pass).unwrap();
+			shader.set_uniform("Texture".into(), Uniform::Texture(input));
+			shader.bind(&self.gl, &self.textures);
+			self.gl.draw_fullscreen(shader.program, true);
+
+			if let Some(target) = output {
+				input = self.render_target_texture(target);
+			}
+		}
+
+		// The passes bound all sorts of textures
+		self.current_texture.set(None);
+	}
+
+	fn post_targets(&mut self, size: PixelSize) -> [RenderTargetId; 2] {
+		if let Some(targets) = self.post_targets {
+			let target = self.render_targets.get(&targets[0]).unwrap();
+
+			if target.framebuffer.size == size {
+				return targets;
+			}
+
+			for rtid in targets {
+				self.delete_render_target(rtid);
+			}
+		}
+
+		let targets = [
+			self.create_render_target(size),
+			self.create_render_target(size),
+		];
+		self.post_targets = Some(targets);
+
+		// Creating framebuffers unbinds whatever we were drawing to
+		self.bind_render_target(self.current_render_target);
+
+		targets
+	}
+
+	pub fn make_font<P: AsRef<FilePath>>(&mut self, path: P) -> Result<FontId, FontError> {
+		self.make_font_with(path, FontOptions::default())
+	}
+
+	pub fn make_font_with<P: AsRef<FilePath>>(
+		&mut self,
+		path: P,
+		options: FontOptions,
+	) -> Result<FontId, FontError> {
+		self.make_font_family_with(&[path], options)
+	}
+
+	/// Make a font from a file that's already in memory. Handy with
+	/// `include_bytes!`.
+	pub fn make_font_from_bytes(&mut self, bytes: &[u8]) -> Result<FontId, FontError> {
+		self.make_font_from_bytes_with(bytes, FontOptions::default())
+	}
+
+	pub fn make_font_from_bytes_with(
+		&mut self,
+		bytes: &[u8],
+		options: FontOptions,
+	) -> Result<FontId, FontError> {
+		Ok(self.insert_font(SmittenFont::from_bytes(bytes, options)?))
+	}
+
+	/// Make one font out of several files. Glyphs missing from the first are
+	/// looked for in the next, and so on. Fallbacks are scaled to match the
+	/// first font so that mixed lines sit evenly. There has to be at least one
+	/// file.
+	pub fn make_font_family<P: AsRef<FilePath>>(
+		&mut self,
+		paths: &[P],
+	) -> Result<FontId, FontError> {
+		self.make_font_family_with(paths, FontOptions::default())
+	}
+
+	pub fn make_font_family_with<P: AsRef<FilePath>>(
+		&mut self,
+		paths: &[P],
+		options: FontOptions,
+	) -> Result<FontId, FontError> {
+		Ok(self.insert_font(SmittenFont::family_with(paths, options)?))
+	}
+
+	/// Make a font that's stored as distance fields rather than pixels. It
+	/// stays sharp however big it's drawn, and can have outlines and glows,
+	/// see [TextEffects].
+	pub fn make_sdf_font<P: AsRef<FilePath>>(&mut self, path: P) -> Result<FontId, FontError> {
+		self.make_font_with(path, FontOptions::distance_field())
+	}
+
+	pub fn make_sdf_font_family<P: AsRef<FilePath>>(
+		&mut self,
+		paths: &[P],
+	) -> Result<FontId, FontError> {
+		self.make_font_family_with(paths, FontOptions::distance_field())
+	}
+
+	/// Load an AngelCode BMFont `.fnt`, text or XML, with its page images
+	/// next to it. Text is as big as the font was drawn at a scale of 1.0.
+	pub fn make_bitmap_font<P: AsRef<FilePath>>(
+		&mut self,
+		path: P,
+	) -> Result<FontId, BitmapFontError> {
+		let bitmap = BitmapFont::bmfont(&self.gl, path, Filter::Nearest)?;
+		Ok(self.insert_font(SmittenFont::from_bitmap(bitmap, Filter::Nearest)))
+	}
+
+	/// Load an image font made of a grid of `cell_width` by `cell_height`
+	/// pixel cells, one for every character in `charset` in reading order.
+	pub fn make_grid_font<P: AsRef<FilePath>>(
+		&mut self,
+		path: P,
+		charset: &str,
+		cell_width: u32,
+		cell_height: u32,
+	) -> Result<FontId, BitmapFontError> {
+		let bitmap = BitmapFont::grid(
+			&self.gl,
+			path,
+			charset,
+			cell_width,
+			cell_height,
+			Filter::Nearest,
+		)?;
+		Ok(self.insert_font(SmittenFont::from_bitmap(bitmap, Filter::Nearest)))
+	}
+
+	fn insert_font(&mut self, font: SmittenFont) -> FontId {
+		font.preload(&self.gl);
+
 		let id = self.next_fontid;
 
 		self.fonts.insert(id, font);
@@ -261,6 +810,13 @@ impl Smitten {
 		id
 	}
 
+	/// Look in `fallback`, and all of its fallbacks, for glyphs that `font`
+	/// doesn't have.
+	pub fn add_fallback(&mut self, font: FontId, fallback: FontId) {
+		let fallback = self.fonts.get(&fallback).unwrap().faces().to_vec();
+		self.fonts.get_mut(&font).unwrap().add_fallback(fallback);
+	}
+
 	pub fn write<S: Into<String>, P: Into<Anchored>>(
 		&self,
 		font: FontId,
@@ -269,78 +825,288 @@ impl Smitten {
 		color: Color,
 		scale: f32,
 	) {
-		let size = 64.0 * scale;
-		let string = text.into();
-		let font = self.fonts.get(&font).unwrap();
+		let laid = self.layout_text(&TextLayout::new(font, text, scale));
+		self.draw_text(&laid, pos, color)
+	}
 
+	/// Measure text without drawing it. This is the same layout that
+	/// [Smitten::write] uses, so what's measured is what gets drawn.
+	pub fn measure_text<S: Into<String>>(&self, font: FontId, text: S, scale: f32) -> TextMetrics {
+		self.layout_text(&TextLayout::new(font, text, scale))
+			.metrics()
+	}
+
+	/// Lay text out into lines without drawing it. The bounds of the result
+	/// can be used to size things around the text before drawing it with
+	/// [Smitten::draw_text].
+	pub fn layout_text(&self, layout: &TextLayout) -> LaidOutText {
+		text::layout(&self.fonts, layout, self.gl.transform.mur_size)
+	}
+
+	/// Write text made of differently styled spans. Spans without a color of
+	/// their own are drawn in `color`.
+	pub fn write_rich<P: Into<Anchored>>(&self, text: &RichText, pos: P, color: Color) {
+		let laid = self.layout_text(&text.layout());
+		self.draw_text(&laid, pos, color)
+	}
+
+	/// Write text, calling `f` for every glyph so it can be moved, colored or
+	/// scaled on its own. `f` gets the index of the glyph, the character it's
+	/// for, and how it'll be drawn.
+	///
+	/// ```ignore
+	/// // Wavy text
+	/// smitten.write_with(font, "Hello!", (0, 0), Color::WHITE, 1.0, |idx, _ch, glyph| {
+	///     glyph.offset.y = (time * 4.0 + idx as f32 * 0.5).sin() * 0.1;
+	/// });
+	/// ```
+	pub fn write_with<S, P, F>(&self, font: FontId, text: S, pos: P, color: Color, scale: f32, f: F)
+	where
+		S: Into<String>,
+		P: Into<Anchored>,
+		F: FnMut(usize, char, &mut GlyphDraw),
+	{
+		let laid = self.layout_text(&TextLayout::new(font, text, scale));
+		self.draw_text_with(&laid, pos, color, &TextEffects::default(), f)
+	}
+
+	pub fn draw_text<P: Into<Anchored>>(&self, text: &LaidOutText, pos: P, color: Color) {
+		self.draw_text_with_effects(text, pos, color, &TextEffects::default())
+	}
+
+	pub fn draw_text_with_effects<P: Into<Anchored>>(
+		&self,
+		text: &LaidOutText,
+		pos: P,
+		color: Color,
+		effects: &TextEffects,
+	) {
+		self.draw_text_with(text, pos, color, effects, |_, _, _| ())
+	}
+
+	/// Draw text with effects, calling `f` for every glyph like
+	/// [Smitten::write_with] does.
+	pub fn draw_text_with<P, F>(
+		&self,
+		text: &LaidOutText,
+		pos: P,
+		color: Color,
+		effects: &TextEffects,
+		mut f: F,
+	) where
+		P: Into<Anchored>,
+		F: FnMut(usize, char, &mut GlyphDraw),
+	{
 		// We're about to override this
 		self.current_texture.set(None);
 
-		self.gl.bind_program();
-		self.gl
-			.set_texture_coloring_uniform(TextureColoring::MixTexture);
-		self.gl.set_color_uniform(color);
+		let span_color = |span: usize| text.spans[span].color.unwrap_or(color);
 
-		unsafe { font.packed.texture.bind(&self.gl) };
+		let text_dim = text.dimensions;
+		let text_hdim = text_dim / 2;
 
-		// New layout code
-		let mut ascent = 0.0f32;
-		let mut descent = 0.0f32;
-		let mut off_x = 0.0f32;
+		let mur_size = self.gl.transform.mur_size;
+		let unmur = |f: f32| -> f32 { f * mur_size as f32 };
 
-		for ch in string.chars() {
-			let metrics = font.font.metrics(ch, size);
+		let pos = pos.into().resolve(text.bounds(), &self.gl.transform);
+		let pos = pos.operation(unmur);
 
-			// ascent is the bit of the glyph above the baseline. ymin is
-			// negative if there is descent.
-			ascent = ascent.max(metrics.height as f32 + metrics.ymin as f32);
-			descent = descent.min(metrics.ymin as f32);
+		let draws: Vec<GlyphDraw> = text
+			.glyphs
+			.iter()
+			.zip(text.chars())
+			.enumerate()
+			.map(|(idx, (laid, ch))| {
+				let mut draw = GlyphDraw {
+					offset: Vec2::ZERO,
+					color: span_color(laid.span),
+					scale: 1.0,
+				};
+
+				f(idx, ch, &mut draw);
+				draw
+			})
+			.collect();
+
+		// Outline and glow widths in the distance field's units, which depend
+		// on how big the span is drawn.
+		let distance = |span: &SpanStyle, murs: f32| -> f32 {
+			let font = self.fonts.get(&span.font).unwrap();
+			let scale = font.pixel_size(span.scale) / font.raster_size();
+			let pixels = unmur(murs) / scale;
+
+			(pixels / (SDF_SPREAD * 2) as f32).clamp(0.0, 0.5)
+		};
 
-			off_x += metrics.advance_width;
+		if let Some(shadow) = effects.shadow {
+			let offset = pos + shadow.offset.operation(unmur);
+			self.draw_glyphs(text, offset, &draws, false, |span, draw| {
+				let style = DistanceTextStyle {
+					softness: distance(span, shadow.softness),
+					..Default::default()
+				};
+
+				let mut color = shadow.color;
+				color.a *= draw.color.a;
+				(color, style)
+			});
 		}
 
-		let mur_size = self.gl.transform.mur_size;
-		let mur = |f: f32| -> f32 { f / mur_size as f32 };
-		let unmur = |f: f32| -> f32 { f * mur_size as f32 };
+		// Bitmaps can't draw their own outline so we stamp them around where
+		// they'll be in every direction.
+		if let Some((outline, width)) = effects.outline {
+			for step in 0..8 {
+				let angle = step as f32 * std::f32::consts::FRAC_PI_4;
+				let offset = Vec2::new(angle.cos(), angle.sin()) * unmur(width);
+
+				self.draw_glyphs(text, pos + offset, &draws, true, |_, draw| {
+					let mut color = outline;
+					color.a *= draw.color.a;
+					(color, DistanceTextStyle::default())
+				});
+			}
+		}
+
+		self.draw_glyphs(text, pos, &draws, false, |span, draw| {
+			let mut style = DistanceTextStyle::default();
+
+			if let Some((color, width)) = effects.outline {
+				style.outline = color;
+				style.outline_width = distance(span, width);
+			}
+
+			if let Some((color, radius)) = effects.glow {
+				style.glow = color;
+				style.glow_width = distance(span, radius).min(0.5 - style.outline_width);
+			}
+
+			(draw.color, style)
+		});
 
-		let width = off_x;
-		let height = ascent + descent.abs();
-		let text_dim = Vec2::new(width, height);
+		// Underlines and strikethroughs go over the glyphs
+		for decoration in &text.decorations {
+			let center = Vec2::new(
+				decoration.start.x + decoration.width / 2.0 - text_hdim.x,
+				text_dim.y - decoration.start.y - text_hdim.y,
+			);
+
+			self.rect(
+				(center + pos) / mur_size,
+				Vec2::new(decoration.width, decoration.thickness) / mur_size,
+				span_color(decoration.span),
+			);
+		}
+	}
+
+	/// Draw the glyphs of `text` centered on `pos`, in pixels. `style` gives
+	/// the color and distance field effects of every glyph. If `only_bitmaps`
+	/// is set, glyphs of distance field fonts are skipped.
+	fn draw_glyphs<F>(
+		&self,
+		text: &LaidOutText,
+		pos: Vec2,
+		draws: &[GlyphDraw],
+		only_bitmaps: bool,
+		style: F,
+	) where
+		F: Fn(&SpanStyle, &GlyphDraw) -> (Color, DistanceTextStyle),
+	{
+		self.gl.bind_program();
+		self.gl
+			.set_texture_coloring_uniform(TextureColoring::MixTexture);
+
+		let text_dim = text.dimensions;
 		let text_hdim = text_dim / 2;
-		let baseline = descent.abs();
+		let mur_size = self.gl.transform.mur_size;
 
-		let pos = pos
-			.into()
-			.resolve(text_dim.operation(mur), &self.gl.transform);
-		let pos = pos.operation(unmur);
+		// What the distance field program was last set up for
+		let mut distance_setup = None;
 
-		let mut offset_x = 0.0;
-		for ch in string.chars() {
-			let metrics = font.font.metrics(ch, size);
+		for (laid, draw) in text.glyphs.iter().zip(draws) {
+			let span = &text.spans[laid.span];
+			let font = self.fonts.get(&span.font).unwrap();
 
-			// Dimensioning
-			let dim = Vec2::new(metrics.width as f32, metrics.height as f32);
+			if only_bitmaps && font.kind() != FontKind::Bitmap {
+				continue;
+			}
 
-			let gl_dim = self.gl.transform.pixel_vec_to_opengl(dim);
+			let size = font.pixel_size(span.scale);
+			let metrics = font.metrics(laid.glyph, size);
 
-			// Positioning
-			let x = (metrics.width as f32 / 2.0) + offset_x + metrics.xmin as f32;
-			let y = (metrics.height as f32 / 2.0) + baseline + metrics.ymin as f32;
+			// Nothing to draw for things like spaces
+			if metrics.width == 0 || metrics.height == 0 {
+				continue;
+			}
 
-			offset_x += metrics.advance_width;
+			let cached = font.glyph(&self.gl, laid.glyph);
+			// Too big for the atlas, so there's nothing to draw
+			if cached.texture_dimensions == Vec2::ZERO {
+				continue;
+			}
 
-			let glyph_pos = Vec2::new(x - text_hdim.x, y - text_hdim.y);
+			// Where the image sits from the pen, and how big it is, in pixels
+			let (offset, dim) = match font.kind() {
+				FontKind::Bitmap => (
+					Vec2::new(metrics.xmin as f32, metrics.ymin as f32),
+					Vec2::new(metrics.width as f32, metrics.height as f32),
+				),
+				// Distance fields are bigger than the glyph so they have room
+				// to spread out
+				FontKind::DistanceField => {
+					let scale = size / font.raster_size();
+					(cached.offset * scale, cached.size * scale)
+				}
+			};
+
+			// Positioning. The layout's Y goes down from the top, ours goes up
+			// from the bottom.
+			let x = (dim.x / 2.0) + laid.position.x + offset.x;
+			let y = (dim.y / 2.0) + (text_dim.y - laid.position.y) + offset.y;
+
+			let dim = dim * draw.scale;
+			let mut glyph_pos =
+				Vec2::new(x - text_hdim.x, y - text_hdim.y) + draw.offset * mur_size + pos;
+
+			// Line the bottom left corner up with a pixel on the screen
+			if font.pixel_snap() {
+				let screen_hdim = self.gl.transform.screen_vec / 2;
+				let corner = glyph_pos - dim / 2 + screen_hdim;
+				glyph_pos = corner.operation(f32::round) + dim / 2 - screen_hdim;
+			}
 
-			let gl_pos = self.gl.transform.pixel_vec_to_opengl(glyph_pos + pos);
+			let gl_pos = self.gl.transform.pixel_vec_to_opengl(glyph_pos);
+			let gl_dim = self.gl.transform.pixel_vec_to_opengl(dim);
 
-			self.gl.gen_draw_rectangle_raw_coords(
-				gl_pos,
-				gl_dim,
-				&font.packed.characters.get(&ch).unwrap().rect,
-			)
-		}
+			font.bind_page(&self.gl, laid.glyph, cached.page);
 
-		// End new layour code
+			match font.kind() {
+				FontKind::Bitmap => {
+					let (color, _) = style(span, draw);
+					if self.current_color.get() != Some(color) {
+						self.gl.set_color_uniform(color);
+						self.current_color.set(Some(color));
+					}
+
+					self.gl
+						.set_texture_transform(cached.texture_position, cached.texture_dimensions);
+					self.gl.draw_rectangle_raw_coords(gl_pos, gl_dim)
+				}
+				FontKind::DistanceField => {
+					let (color, distance_style) = style(span, draw);
+					if distance_setup != Some((color, distance_style)) {
+						self.gl.set_distance_text_style(color, distance_style);
+						distance_setup = Some((color, distance_style));
+					}
+
+					self.gl.draw_distance_glyph(
+						gl_pos,
+						gl_dim,
+						cached.texture_position,
+						cached.texture_dimensions,
+					)
+				}
+			}
+		}
 	}
 
 	// Draw a rectangle at `pos` murs (center) which is `dim` murs in dimension.
@@ -355,25 +1121,157 @@ impl Smitten {
 			Draw::Color(c) => {
 				self.gl.set_texture_coloring_uniform(TextureColoring::Color);
 
-				if self.current_color.get() != c {
+				if self.current_color.get() != Some(c) {
 					self.gl.set_color_uniform(c);
-					self.current_color.set(c);
+					self.current_color.set(Some(c));
 				}
 			}
-			Draw::Texture(tid) => match self.current_texture.get() {
-				Some(cur) if cur == tid => (),
-				Some(_) => self.bind_texture(tid),
-				None => self.bind_texture(tid),
-			},
+			Draw::Texture(tid) => {
+				match self.current_texture.get() {
+					Some(cur) if cur == tid => (),
+					Some(_) => self.bind_texture(tid),
+					None => self.bind_texture(tid),
+				}
+
+				self.set_texture_transform(tid, Vec2::ZERO, Vec2::new(1.0, 1.0));
+			}
+			Draw::TextureUv {
+				texture,
+				offset,
+				scale,
+			} => {
+				match self.current_texture.get() {
+					Some(cur) if cur == texture => (),
+					_ => self.bind_texture(texture),
+				}
+
+				self.set_texture_transform(texture, offset, scale);
+			}
+			Draw::Shader(sid) => {
+				let shader = self.shaders.get(&sid).unwrap();
+				shader.bind(&self.gl, &self.textures);
+				self.gl
+					.draw_rectangle_user_program(shader.program, pos.into(), dim.into());
+
+				// Texture uniforms may have bound over our texture
+				self.current_texture.set(None);
+				return;
+			}
+			Draw::Gradient(gradient) => {
+				self.set_gradient(&gradient);
+				self.gl
+					.set_texture_coloring_uniform(TextureColoring::Gradient);
+			}
 		}
 
 		self.gl.draw_rectangle(pos.into(), dim.into());
 
-		if let Draw::Color(_) = draw {
+		if !matches!(draw, Draw::Texture(_) | Draw::TextureUv { .. }) {
 			self.gl.set_texture_coloring_uniform(self.texture_coloring);
 		}
 	}
 
+	/// Hand a gradient's stops to the shaders
+	fn set_gradient(&self, gradient: &Gradient) {
+		match gradient {
+			Gradient::Linear { from, to, stops } => self.gl.set_gradient(
+				GradientKind::Linear,
+				[from.x, from.y, to.x, to.y],
+				stops.as_slice(),
+			),
+			Gradient::Radial {
+				center,
+				radius,
+				stops,
+			} => self.gl.set_gradient(
+				GradientKind::Radial,
+				[center.x, center.y, *radius, 0.0],
+				stops.as_slice(),
+			),
+			Gradient::Corners {
+				top_left,
+				top_right,
+				bottom_left,
+				bottom_right,
+			} => self.gl.set_gradient(
+				GradientKind::Corners,
+				[0.0; 4],
+				&[
+					(0.0, *bottom_left),
+					(0.0, *bottom_right),
+					(0.0, *top_left),
+					(0.0, *top_right),
+				],
+			),
+		}
+	}
+
+	/// Draw the outline of a path
+	pub fn stroke_path<C: Into<Color>>(&self, path: &Path, style: StrokeStyle, color: C) {
+		let triangles = path.stroke(&style, self.gl.transform.mur_size as f32);
+		self.draw_triangles(&triangles, color.into());
+	}
+
+	/// Draw the inside of a path
+	pub fn fill_path<C: Into<Color>>(&self, path: &Path, rule: FillRule, color: C) {
+		let triangles = path.fill(rule, self.gl.transform.mur_size as f32);
+		self.draw_triangles(&triangles, color.into());
+	}
+
+	fn draw_triangles(&self, triangles: &[Vec2], color: Color) {
+		self.gl.set_texture_coloring_uniform(TextureColoring::Color);
+
+		if self.current_color.get() != Some(color) {
+			self.gl.set_color_uniform(color);
+			self.current_color.set(Some(color));
+		}
+
+		self.gl.draw_triangles(triangles);
+		self.gl.set_texture_coloring_uniform(self.texture_coloring);
+	}
+
+	/// Repeat a texture across a rectangle, once every `tile_size` murs. Tiles
+	/// start from the top left corner.
+	pub fn tile<P, D, T>(&self, pos: P, dim: D, texture: TextureId, tile_size: T)
+	where
+		P: Into<Vec2>,
+		D: Into<Vec2>,
+		T: Into<Vec2>,
+	{
+		self.tile_scrolled(pos, dim, texture, tile_size, Vec2::ZERO)
+	}
+
+	/// [Smitten::tile], but with the tiles moved by `scroll` murs. Change the
+	/// scroll every frame to move a floor or a parallax background along.
+	pub fn tile_scrolled<P, D, T, S>(
+		&self,
+		pos: P,
+		dim: D,
+		texture: TextureId,
+		tile_size: T,
+		scroll: S,
+	) where
+		P: Into<Vec2>,
+		D: Into<Vec2>,
+		T: Into<Vec2>,
+		S: Into<Vec2>,
+	{
+		let dim = dim.into();
+		let tile_size = tile_size.into();
+		let scroll = scroll.into();
+
+		self.rect(
+			pos,
+			dim,
+			Draw::TextureUv {
+				texture,
+				// Texture coordinates go down, murs go up
+				offset: Vec2::new(-scroll.x, scroll.y) / tile_size,
+				scale: dim / tile_size,
+			},
+		)
+	}
+
 	pub fn anchored_rect<A, D, R>(&self, pos: A, dim: D, draw: R)
 	where
 		A: Into<Anchored>,
@@ -385,18 +1283,263 @@ impl Smitten {
 		self.rect(pos, dim, draw)
 	}
 
+	/// Draw a texture cut into a 3x3 grid so that its corners stay the same
+	/// size however big `dim` is. Panels and buttons with borders that
+	/// shouldn't stretch. If `dim` is too small for the corners they shrink
+	/// to fit.
+	pub fn nine_slice<A, D>(&self, pos: A, dim: D, texture: TextureId, slice: NineSlice)
+	where
+		A: Into<Anchored>,
+		D: Into<Vec2>,
+	{
+		let dim = dim.into();
+		let center = pos.into().resolve(dim, &self.gl.transform);
+		let tex = self.texture(texture);
+		let (tex_size, flipped) = (Vec2::new(tex.width as f32, tex.height as f32), tex.flipped);
+		let (rx, ry, rw, rh) = slice.region.unwrap_or((0.0, 0.0, tex_size.x, tex_size.y));
+
+		// Insets can't reach past each other or out of the region
+		let clamp = |inset: f32, space: f32| inset.min(space).max(0.0);
+		let (inset_left, inset_top) = (clamp(slice.left, rw), clamp(slice.top, rh));
+		let inset_right = clamp(slice.right, rw - inset_left);
+		let inset_bottom = clamp(slice.bottom, rh - inset_top);
+
+		// Texture pixels, along each axis, of the three columns and rows
+		let columns = [inset_left, rw - inset_left - inset_right, inset_right];
+		let rows = [inset_top, rh - inset_top - inset_bottom, inset_bottom];
+
+		// Corners are as many murs as they are pixels, unless they don't fit
+		let mur_size = self.gl.transform.mur_size as f32;
+		let fit = |near: f32, far: f32, space: f32| {
+			let size = (near + far) / mur_size;
+			let shrink = if size > space { space / size } else { 1.0 };
+			(near / mur_size * shrink, far / mur_size * shrink)
+		};
+		let (left, right) = fit(columns[0], columns[2], dim.x);
+		let (top, bottom) = fit(rows[0], rows[2], dim.y);
+		let widths = [left, dim.x - left - right, right];
+		let heights = [top, dim.y - top - bottom, bottom];
+
+		match self.current_texture.get() {
+			Some(cur) if cur == texture => (),
+			_ => self.bind_texture(texture),
+		}
+
+		let top_left = center + Vec2::new(-dim.x, dim.y) / 2.0;
+		let mut tiling = false;
+		for row in 0..3 {
+			let y = heights[..row].iter().sum::<f32>();
+			let v = ry + rows[..row].iter().sum::<f32>();
+
+			for column in 0..3 {
+				let x = widths[..column].iter().sum::<f32>();
+				let u = rx + columns[..column].iter().sum::<f32>();
+				let fill = match (row, column) {
+					(1, 1) => slice.center,
+					(1, _) | (_, 1) => slice.edges,
+					_ => SliceFill::Stretch,
+				};
+
+				let size = Vec2::new(widths[column], heights[row]);
+				if size.x <= 0.0 || size.y <= 0.0 {
+					continue;
+				}
+
+				let native = Vec2::new(columns[column], rows[row]);
+				let uv = Vec2::new(u / tex_size.x, v / tex_size.y);
+				let uv_size = Vec2::new(native.x / tex_size.x, native.y / tex_size.y);
+
+				match fill {
+					// One rectangle that wraps around inside of its part of
+					// the texture as many times as it fits
+					SliceFill::Tile if native.x > 0.0 && native.y > 0.0 => {
+						let region = match flipped {
+							true => (
+								Vec2::new(uv.x, 1.0 - uv.y),
+								Vec2::new(uv_size.x, -uv_size.y),
+							),
+							false => (uv, uv_size),
+						};
+
+						self.gl.set_tile_region(Some(region));
+						self.gl
+							.set_texture_transform(Vec2::ZERO, size * mur_size / native);
+						tiling = true;
+					}
+					_ => {
+						if tiling {
+							self.gl.set_tile_region(None);
+							tiling = false;
+						}
+
+						self.set_texture_transform(texture, uv, uv_size);
+					}
+				}
+
+				let corner = top_left + Vec2::new(x, -y);
+				self.gl
+					.draw_rectangle(corner + Vec2::new(size.x, -size.y) / 2.0, size);
+			}
+		}
+
+		if tiling {
+			self.gl.set_tile_region(None);
+		}
+	}
+
+	/// Only draw inside of the rectangle at `pos` murs (center) which is `dim`
+	/// murs in dimension until the matching [Smitten::pop_clip]. Clips nest;
+	/// drawing is restricted to where they all overlap.
+	pub fn push_clip<P, D>(&self, pos: P, dim: D)
+	where
+		P: Into<Vec2>,
+		D: Into<Vec2>,
+	{
+		let pos = pos.into();
+		let hdim = dim.into() / 2;
+
+		let bottom_left = self.gl.transform.vec_to_pixels(pos - hdim);
+		let top_right = self.gl.transform.vec_to_pixels(pos + hdim);
+
+		let mut left = bottom_left.x.round() as i32;
+		let mut bottom = bottom_left.y.round() as i32;
+		let mut right = top_right.x.round() as i32;
+		let mut top = top_right.y.round() as i32;
+
+		let mut stack = self.clip_stack.borrow_mut();
+		if let Some((x, y, width, height)) = stack.last().copied() {
+			left = left.max(x);
+			bottom = bottom.max(y);
+			right = right.min(x + width);
+			top = top.min(y + height);
+		}
+
+		let clip = (left, bottom, (right - left).max(0), (top - bottom).max(0));
+		stack.push(clip);
+		self.gl.set_scissor(Some(clip));
+	}
+
+	pub fn push_anchored_clip<A, D>(&self, pos: A, dim: D)
+	where
+		A: Into<Anchored>,
+		D: Into<Vec2>,
+	{
+		let dim = dim.into();
+		let pos = pos.into().resolve(dim, &self.gl.transform);
+		self.push_clip(pos, dim)
+	}
+
+	pub fn pop_clip(&self) {
+		let mut stack = self.clip_stack.borrow_mut();
+		stack.pop();
+		self.gl.set_scissor(stack.last().copied());
+	}
+
+	/// Only draw where the shapes drawn in `f` cover until the matching
+	/// [Smitten::pop_mask]. Anything can be drawn as a mask, fully transparent
+	/// pixels don't count. Masks nest like clips do.
+	pub fn push_mask<F>(&self, f: F)
+	where
+		F: FnOnce(&Smitten),
+	{
+		let depth = self.mask_depth.get();
+
+		self.gl.start_mask(depth);
+		f(self);
+		self.gl.finish_mask(depth);
+
+		self.mask_depth.set(depth + 1);
+	}
+
+	pub fn pop_mask(&self) {
+		let depth = self.mask_depth.get();
+
+		if depth > 0 {
+			let clip = self.clip_stack.borrow().last().copied();
+			self.gl.remove_mask(depth, clip);
+			self.mask_depth.set(depth - 1);
+		}
+	}
+
 	pub fn sdf(&self, sdf: SignedDistance) {
-		self.gl.draw_sdf(sdf)
+		self.gl.draw_sdf(sdf, &SdfStyle::default())
+	}
+
+	/// Draw a signed distance shape with a stroke or a shadow. Give the shape
+	/// a transparent color to only draw its stroke.
+	pub fn sdf_styled(&self, sdf: SignedDistance, style: SdfStyle) {
+		self.gl.draw_sdf(sdf, &style)
+	}
+
+	/// Draw many signed distance shapes at once, which is a lot faster than
+	/// calling [Smitten::sdf] for each of them. Good for particles.
+	pub fn sdf_batch(&self, sdfs: &[SignedDistance]) {
+		self.gl.draw_sdf_batch(sdfs)
+	}
+
+	/// Draw signed distance shapes that have been put together with unions,
+	/// subtractions, and transforms. It's filled all at once, with a color or
+	/// a gradient. Nothing is drawn if the shape is too big, see
+	/// [SdfShape::check].
+	pub fn sdf_shape<F: Into<SdfFill>>(
+		&self,
+		shape: &SdfShape,
+		fill: F,
+		style: SdfStyle,
+	) -> Result<(), SdfShapeError> {
+		match fill.into() {
+			SdfFill::Color(color) => self.gl.draw_sdf_shape(shape, color, &style),
+			SdfFill::Gradient(gradient) => self.with_sdf_gradient(&gradient, || {
+				self.gl.draw_sdf_shape(shape, Color::WHITE, &style)
+			}),
+		}
+	}
+
+	/// Draw a signed distance shape filled with something other than its own
+	/// color, like a gradient. Gradient positions are across the rectangle
+	/// the shape fits in.
+	pub fn sdf_filled<F: Into<SdfFill>>(&self, sdf: SignedDistance, fill: F, style: SdfStyle) {
+		match fill.into() {
+			SdfFill::Color(color) => self.gl.draw_sdf(sdf.with_color(color), &style),
+			SdfFill::Gradient(gradient) => {
+				self.with_sdf_gradient(&gradient, || self.gl.draw_sdf(sdf, &style))
+			}
+		}
+	}
+
+	fn with_sdf_gradient<T, F: FnOnce() -> T>(&self, gradient: &Gradient, f: F) -> T {
+		self.set_gradient(gradient);
+		self.gl.set_sdf_gradient(true);
+		let result = f();
+		self.gl.set_sdf_gradient(false);
+
+		result
+	}
+
+	/// Panics if there's no texture with that id
+	fn texture(&self, tid: TextureId) -> &Texture {
+		match self.textures.get(&tid) {
+			Some(tex) => tex,
+			None => panic!("Unknown TextureId {:?}", tid),
+		}
 	}
 
 	fn bind_texture(&self, tid: TextureId) {
 		self.gl.bind_program();
-		match self.textures.get(&tid) {
-			Some(tex) => {
-				unsafe { tex.bind(&self.gl) }
-				self.current_texture.set(Some(tid));
-			}
-			None => todo!(),
+		unsafe { self.texture(tid).bind(&self.gl) }
+		self.current_texture.set(Some(tid));
+	}
+
+	/// Draw the part of the texture from `offset` that's `scale` big, in
+	/// texture coordinates from the top left, whichever way up it is
+	fn set_texture_transform(&self, tid: TextureId, offset: Vec2, scale: Vec2) {
+		if self.texture(tid).flipped {
+			self.gl.set_texture_transform(
+				Vec2::new(offset.x, 1.0 - offset.y),
+				Vec2::new(scale.x, -scale.y),
+			)
+		} else {
+			self.gl.set_texture_transform(offset, scale)
 		}
 	}
 
@@ -434,10 +1577,22 @@ pub enum SmittenEvent {
 	MouseUp { button: MouseButton },
 }
 
+/// What to fill a rectangle with
 #[derive(Copy, Clone, Debug)]
 pub enum Draw {
 	Color(Color),
 	Texture(TextureId),
+	/// A texture that's moved and scaled. The texture coordinates of the
+	/// rectangle are `offset + position * scale`, where position goes from
+	/// (0, 0) at the top left to (1, 1) at the bottom right, so a scale of two
+	/// repeats the texture twice across. Animate `offset` to scroll it.
+	TextureUv {
+		texture: TextureId,
+		offset: Vec2,
+		scale: Vec2,
+	},
+	Shader(ShaderId),
+	Gradient(Gradient),
 }
 
 impl From<Color> for Draw {
@@ -452,6 +1607,18 @@ impl From<TextureId> for Draw {
 	}
 }
 
+impl From<ShaderId> for Draw {
+	fn from(sid: ShaderId) -> Draw {
+		Draw::Shader(sid)
+	}
+}
+
+impl From<Gradient> for Draw {
+	fn from(gradient: Gradient) -> Draw {
+		Draw::Gradient(gradient)
+	}
+}
+
 #[derive(Copy, Clone, Debug)]
 pub enum Anchored {
 	Vertical {
@@ -0,0 +1,60 @@
+/// How to cut up a texture for [crate::Smitten::nine_slice]. The insets are
+/// how far in from each edge of the texture the corners go, in texture
+/// pixels. Corners are drawn at their size in pixels and the edges and
+/// center grow to fill the rest.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct NineSlice {
+	pub left: f32,
+	pub right: f32,
+	pub top: f32,
+	pub bottom: f32,
+	/// The part of the texture to slice up as `(x, y, width, height)` in
+	/// pixels from the top left. The whole texture if it's `None`.
+	pub region: Option<(f32, f32, f32, f32)>,
+	pub edges: SliceFill,
+	pub center: SliceFill,
+}
+
+/// How the edges and the center of a [NineSlice] fill their space
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum SliceFill {
+	#[default]
+	Stretch,
+	/// Repeat at the size they are in the texture, cutting off the last one
+	Tile,
+}
+
+impl NineSlice {
+	pub fn new(left: f32, right: f32, top: f32, bottom: f32) -> Self {
+		Self {
+			left,
+			right,
+			top,
+			bottom,
+			region: None,
+			edges: SliceFill::Stretch,
+			center: SliceFill::Stretch,
+		}
+	}
+
+	/// The same inset on every side
+	pub fn uniform(inset: f32) -> Self {
+		Self::new(inset, inset, inset, inset)
+	}
+
+	/// Only use part of the texture, like a panel in a UI atlas
+	pub fn region(mut self, x: f32, y: f32, width: f32, height: f32) -> Self {
+		self.region = Some((x, y, width, height));
+		self
+	}
+
+	pub fn edges(mut self, fill: SliceFill) -> Self {
+		self.edges = fill;
+		self
+	}
+
+	pub fn center(mut self, fill: SliceFill) -> Self {
+		self.center = fill;
+		self
+	}
+}
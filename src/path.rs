@@ -0,0 +1,593 @@
+use std::f32::consts::PI;
+
+use crate::Vec2;
+
+/// How far, in pixels, flattened curves and round joins are allowed to stray
+/// from the real curve
+const TOLERANCE: f32 = 0.25;
+
+/// Lines and curves to stroke with [crate::Smitten::stroke_path] or fill with
+/// [crate::Smitten::fill_path]. Everything is in murs.
+///
+/// ```ignore
+/// let path = Path::new()
+///     .move_to((0.0, 0.0))
+///     .line_to((4.0, 0.0))
+///     .quad_to((6.0, 2.0), (4.0, 4.0))
+///     .close();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Path {
+	subpaths: Vec<SubPath>,
+}
+
+#[derive(Clone, Debug)]
+struct SubPath {
+	start: Vec2,
+	segments: Vec<Segment>,
+	closed: bool,
+}
+
+/// Where a segment goes from the end of the last one, and its control points
+#[derive(Copy, Clone, Debug)]
+enum Segment {
+	Line(Vec2),
+	Quad(Vec2, Vec2),
+	Cubic(Vec2, Vec2, Vec2),
+}
+
+impl Segment {
+	fn end(&self) -> Vec2 {
+		match *self {
+			Segment::Line(end) | Segment::Quad(_, end) | Segment::Cubic(_, _, end) => end,
+		}
+	}
+}
+
+impl SubPath {
+	fn end(&self) -> Vec2 {
+		self.segments.last().map(Segment::end).unwrap_or(self.start)
+	}
+}
+
+impl Path {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Start a new piece of the path at `point`
+	pub fn move_to<P: Into<Vec2>>(mut self, point: P) -> Self {
+		self.subpaths.push(SubPath {
+			start: point.into(),
+			segments: vec![],
+			closed: false,
+		});
+		self
+	}
+
+	pub fn line_to<P: Into<Vec2>>(self, point: P) -> Self {
+		let point = point.into();
+		self.push(point, Segment::Line(point))
+	}
+
+	/// A quadratic bezier curve bent towards `control`
+	pub fn quad_to<C, P>(self, control: C, point: P) -> Self
+	where
+		C: Into<Vec2>,
+		P: Into<Vec2>,
+	{
+		let control = control.into();
+		self.push(control, Segment::Quad(control, point.into()))
+	}
+
+	pub fn cubic_to<C, D, P>(self, control1: C, control2: D, point: P) -> Self
+	where
+		C: Into<Vec2>,
+		D: Into<Vec2>,
+		P: Into<Vec2>,
+	{
+		let control1 = control1.into();
+		self.push(
+			control1,
+			Segment::Cubic(control1, control2.into(), point.into()),
+		)
+	}
+
+	/// Round the corner made by going to `corner` and then on to `point` with
+	/// an arc of `radius` murs. The path stops at the end of the arc, so draw
+	/// a line to `point` to finish the corner. Works like `arcTo` on an HTML
+	/// canvas.
+	pub fn arc_to<C, P>(self, corner: C, point: P, radius: f32) -> Self
+	where
+		C: Into<Vec2>,
+		P: Into<Vec2>,
+	{
+		let (corner, point) = (corner.into(), point.into());
+		let from = match self.current() {
+			Some(from) => from,
+			None => return self.move_to(corner),
+		};
+
+		let before = (from - corner).normalize_correct();
+		let after = (point - corner).normalize_correct();
+		let cos = before.dot(after);
+
+		// Nothing to round if the lines are straight or one of them is empty
+		if radius <= 0.0 || before == Vec2::ZERO || after == Vec2::ZERO || cos.abs() > 0.9999 {
+			return self.line_to(corner);
+		}
+
+		let half = cos.acos() / 2.0;
+		let tangent = radius / half.tan();
+		let start = corner + before * tangent;
+		let end = corner + after * tangent;
+		let center = corner + (before + after).normalize_correct() * (radius / half.sin());
+
+		let start_angle = angle(start - center);
+		let sweep = shortest(angle(end - center) - start_angle);
+
+		self.line_to(start).arc(center, radius, start_angle, sweep)
+	}
+
+	/// Go back to where this piece of the path started
+	pub fn close(mut self) -> Self {
+		if let Some(subpath) = self.subpaths.last_mut() {
+			subpath.closed = true;
+		}
+		self
+	}
+
+	fn current(&self) -> Option<Vec2> {
+		self.subpaths.last().map(|subpath| match subpath.closed {
+			true => subpath.start,
+			false => subpath.end(),
+		})
+	}
+
+	/// Add a segment to the path, starting at `first` if there isn't one yet
+	fn push(mut self, first: Vec2, segment: Segment) -> Self {
+		match self.current() {
+			None => self = self.move_to(first),
+			// Drawing after closing starts again where we closed to
+			Some(start) if self.subpaths.last().unwrap().closed => self = self.move_to(start),
+			Some(_) => (),
+		}
+
+		self.subpaths.last_mut().unwrap().segments.push(segment);
+		self
+	}
+
+	/// An arc as cubic curves, no more than a quarter circle each
+	fn arc(mut self, center: Vec2, radius: f32, start: f32, sweep: f32) -> Self {
+		let pieces = (sweep.abs() / (PI / 2.0)).ceil().max(1.0);
+		let step = sweep / pieces;
+		let k = 4.0 / 3.0 * (step / 4.0).tan() * radius;
+
+		for piece in 0..pieces as usize {
+			let a0 = start + step * piece as f32;
+			let a1 = a0 + step;
+			let p0 = center + unit(a0) * radius;
+			let p1 = center + unit(a1) * radius;
+
+			self = self.cubic_to(
+				p0 + unit(a0 + PI / 2.0) * k,
+				p1 - unit(a1 + PI / 2.0) * k,
+				p1,
+			);
+		}
+
+		self
+	}
+
+	/// The path as lines, with curves split up finely enough for `mur_size`.
+	/// Each comes with whether it's closed.
+	fn flatten(&self, mur_size: f32) -> Vec<(Vec<Vec2>, bool)> {
+		self.subpaths
+			.iter()
+			// A move with nothing after it doesn't draw anything
+			.filter(|subpath| !subpath.segments.is_empty())
+			.map(|subpath| {
+				let mut points = vec![subpath.start];
+				let mut from = subpath.start;
+
+				for segment in &subpath.segments {
+					match *segment {
+						Segment::Line(to) => points.push(to),
+						Segment::Quad(c, to) => {
+							let bend = (from - c * 2.0 + to).length() * mur_size;
+							let steps = curve_steps(bend / 4.0);
+
+							for step in 1..=steps {
+								let t = step as f32 / steps as f32;
+								let u = 1.0 - t;
+								points.push(from * (u * u) + c * (2.0 * u * t) + to * (t * t));
+							}
+						}
+						Segment::Cubic(c1, c2, to) => {
+							let bend = (from - c1 * 2.0 + c2)
+								.length()
+								.max((c1 - c2 * 2.0 + to).length())
+								* mur_size;
+							let steps = curve_steps(bend * 3.0 / 4.0);
+
+							for step in 1..=steps {
+								let t = step as f32 / steps as f32;
+								let u = 1.0 - t;
+								points.push(
+									from * (u * u * u)
+										+ c1 * (3.0 * u * u * t) + c2 * (3.0 * u * t * t)
+										+ to * (t * t * t),
+								);
+							}
+						}
+					}
+
+					from = segment.end();
+				}
+
+				points.dedup();
+				if subpath.closed && points.len() > 1 && points.first() == points.last() {
+					points.pop();
+				}
+
+				(points, subpath.closed)
+			})
+			.collect()
+	}
+
+	/// Triangles covering the stroke of the path, in murs. Where the stroke
+	/// crosses itself it's covered twice, so see-through strokes will show
+	/// the overlap.
+	pub(crate) fn stroke(&self, style: &StrokeStyle, mur_size: f32) -> Vec<Vec2> {
+		let mut triangles = vec![];
+		let half = style.width / 2.0;
+		let round = |triangles: &mut Vec<Vec2>, center, start, sweep| {
+			fan(triangles, center, half, start, sweep, half * mur_size)
+		};
+
+		for (points, closed) in self.flatten(mur_size) {
+			if points.len() == 1 {
+				// A dot, if the caps give it any size
+				match style.cap {
+					LineCap::Butt => (),
+					LineCap::Round => round(&mut triangles, points[0], 0.0, PI * 2.0),
+					LineCap::Square => quad(
+						&mut triangles,
+						points[0] - Vec2::new(half, half),
+						points[0] + Vec2::new(half, -half),
+						points[0] + Vec2::new(half, half),
+						points[0] + Vec2::new(-half, half),
+					),
+				}
+
+				continue;
+			}
+
+			let count = points.len();
+			let segments = if closed { count } else { count - 1 };
+			let direction = |index: usize| {
+				(points[(index + 1) % count] - points[index % count]).normalize_correct()
+			};
+
+			for index in 0..segments {
+				let mut start = points[index];
+				let mut end = points[(index + 1) % count];
+				let dir = direction(index);
+				let normal = perpendicular(dir) * half;
+
+				if !closed && style.cap == LineCap::Square {
+					if index == 0 {
+						start -= dir * half;
+					}
+					if index == segments - 1 {
+						end += dir * half;
+					}
+				}
+
+				quad(
+					&mut triangles,
+					start + normal,
+					start - normal,
+					end - normal,
+					end + normal,
+				);
+			}
+
+			// Joins go where one segment meets the next
+			let joins = if closed { 0..count } else { 1..count - 1 };
+			for index in joins {
+				let point = points[index];
+				let before = direction(index + count - 1);
+				let after = direction(index);
+				let turn = before.x * after.y - before.y * after.x;
+
+				if turn.abs() < 1e-6 && before.dot(after) > 0.0 {
+					continue;
+				}
+
+				// The gap is on the outside of the turn
+				let side = if turn > 0.0 { -1.0 } else { 1.0 };
+				let outer_before = point + perpendicular(before) * (half * side);
+				let outer_after = point + perpendicular(after) * (half * side);
+
+				match style.join {
+					LineJoin::Round => {
+						let start = angle(outer_before - point);
+						let sweep = shortest(angle(outer_after - point) - start);
+						round(&mut triangles, point, start, sweep);
+					}
+					LineJoin::Miter => {
+						let middle =
+							(perpendicular(before) + perpendicular(after)).normalize_correct();
+						let cos = middle.dot(perpendicular(before));
+						let ratio = 1.0 / cos.max(1e-6);
+
+						if ratio <= style.miter_limit {
+							let tip = point + middle * (half * ratio * side);
+							triangles.extend_from_slice(&[point, outer_before, tip]);
+							triangles.extend_from_slice(&[point, tip, outer_after]);
+						} else {
+							triangles.extend_from_slice(&[point, outer_before, outer_after]);
+						}
+					}
+					LineJoin::Bevel => {
+						triangles.extend_from_slice(&[point, outer_before, outer_after])
+					}
+				}
+			}
+
+			if !closed && style.cap == LineCap::Round {
+				let first = perpendicular(direction(0));
+				let last = perpendicular(direction(count - 2));
+
+				round(&mut triangles, points[0], angle(first), PI);
+				round(&mut triangles, points[count - 1], angle(last) + PI, PI);
+			}
+		}
+
+		triangles
+	}
+
+	/// Triangles covering the inside of the path, in murs. Every piece of the
+	/// path is treated as closed.
+	///
+	/// The path is cut into horizontal slabs wherever an edge starts, ends,
+	/// or crosses another so that no edges cross inside of a slab. Then the
+	/// edges in each slab can be walked left to right, counting the winding,
+	/// and the inside parts drawn as trapezoids.
+	pub(crate) fn fill(&self, rule: FillRule, mur_size: f32) -> Vec<Vec2> {
+		let mut edges = vec![];
+		for (points, _) in self.flatten(mur_size) {
+			for index in 0..points.len() {
+				let a = points[index];
+				let b = points[(index + 1) % points.len()];
+
+				if a.y < b.y {
+					edges.push(Edge {
+						low: a,
+						high: b,
+						winding: 1,
+					});
+				} else if a.y > b.y {
+					edges.push(Edge {
+						low: b,
+						high: a,
+						winding: -1,
+					});
+				}
+			}
+		}
+
+		let mut ys: Vec<f32> = edges.iter().flat_map(|e| [e.low.y, e.high.y]).collect();
+		for (index, a) in edges.iter().enumerate() {
+			for b in &edges[index + 1..] {
+				if let Some(y) = a.crossing(b) {
+					ys.push(y);
+				}
+			}
+		}
+		ys.sort_by(f32::total_cmp);
+		ys.dedup();
+
+		let mut triangles = vec![];
+		let mut crossing = vec![];
+		for slab in ys.windows(2) {
+			let (bottom, top) = (slab[0], slab[1]);
+			let middle = (bottom + top) / 2.0;
+
+			crossing.clear();
+			crossing.extend(
+				edges
+					.iter()
+					.filter(|e| e.low.y < middle && middle < e.high.y)
+					.map(|e| (e.x_at(middle), e.x_at(bottom), e.x_at(top), e.winding)),
+			);
+			crossing.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+			let mut winding = 0;
+			for pair in crossing.windows(2) {
+				let (left, right) = (pair[0], pair[1]);
+				winding += left.3;
+
+				let inside = match rule {
+					FillRule::NonZero => winding != 0,
+					FillRule::EvenOdd => winding % 2 != 0,
+				};
+
+				if inside {
+					quad(
+						&mut triangles,
+						Vec2::new(left.1, bottom),
+						Vec2::new(right.1, bottom),
+						Vec2::new(right.2, top),
+						Vec2::new(left.2, top),
+					);
+				}
+			}
+		}
+
+		triangles
+	}
+}
+
+/// An edge of a filled path, with the ends sorted by y
+struct Edge {
+	low: Vec2,
+	high: Vec2,
+	/// 1 if the path goes up along this edge and -1 if it goes down
+	winding: i32,
+}
+
+impl Edge {
+	fn x_at(&self, y: f32) -> f32 {
+		let t = (y - self.low.y) / (self.high.y - self.low.y);
+		self.low.x + (self.high.x - self.low.x) * t
+	}
+
+	/// The y where the edges cross, if they do somewhere other than their ends
+	fn crossing(&self, other: &Edge) -> Option<f32> {
+		if self.high.y <= other.low.y || other.high.y <= self.low.y {
+			return None;
+		}
+
+		let cross = |a: Vec2, b: Vec2| a.x * b.y - a.y * b.x;
+		let r = self.high - self.low;
+		let s = other.high - other.low;
+		let denominator = cross(r, s);
+
+		if denominator.abs() < 1e-9 {
+			return None;
+		}
+
+		let between = other.low - self.low;
+		let t = cross(between, s) / denominator;
+		let u = cross(between, r) / denominator;
+
+		(t > 0.0 && t < 1.0 && u > 0.0 && u < 1.0).then_some(self.low.y + r.y * t)
+	}
+}
+
+/// How the stroke of a [Path] looks
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StrokeStyle {
+	/// In murs
+	pub width: f32,
+	pub join: LineJoin,
+	pub cap: LineCap,
+	/// How many times longer than half the width a miter can get before it's
+	/// drawn as a bevel instead
+	pub miter_limit: f32,
+}
+
+impl StrokeStyle {
+	pub fn new(width: f32) -> Self {
+		Self {
+			width,
+			join: LineJoin::Miter,
+			cap: LineCap::Butt,
+			miter_limit: 4.0,
+		}
+	}
+
+	pub fn join(mut self, join: LineJoin) -> Self {
+		self.join = join;
+		self
+	}
+
+	pub fn cap(mut self, cap: LineCap) -> Self {
+		self.cap = cap;
+		self
+	}
+
+	pub fn miter_limit(mut self, limit: f32) -> Self {
+		self.miter_limit = limit;
+		self
+	}
+}
+
+/// How the corners of a stroke are drawn
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum LineJoin {
+	/// Continue the edges until they meet in a point
+	#[default]
+	Miter,
+	Round,
+	/// Cut the corner off flat
+	Bevel,
+}
+
+/// How the ends of a stroke are drawn
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum LineCap {
+	/// Stop right at the end
+	#[default]
+	Butt,
+	Round,
+	/// Go past the end by half the width
+	Square,
+}
+
+/// Which parts of a path that crosses itself are inside
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum FillRule {
+	/// Inside if the path goes around it more times one way than the other
+	#[default]
+	NonZero,
+	/// Inside if the path goes around it an odd number of times, so overlaps
+	/// make holes
+	EvenOdd,
+}
+
+fn quad(triangles: &mut Vec<Vec2>, a: Vec2, b: Vec2, c: Vec2, d: Vec2) {
+	triangles.extend_from_slice(&[a, b, c, a, c, d]);
+}
+
+/// A slice of a circle, split finely enough that it looks round at
+/// `pixel_radius`
+fn fan(
+	triangles: &mut Vec<Vec2>,
+	center: Vec2,
+	radius: f32,
+	start: f32,
+	sweep: f32,
+	pixel_radius: f32,
+) {
+	let step = 2.0 * (1.0 - TOLERANCE / pixel_radius.max(TOLERANCE)).acos();
+	let steps = (sweep.abs() / step.max(0.01)).ceil().clamp(1.0, 256.0) as usize;
+
+	let mut last = center + unit(start) * radius;
+	for index in 1..=steps {
+		let next = center + unit(start + sweep * index as f32 / steps as f32) * radius;
+		triangles.extend_from_slice(&[center, last, next]);
+		last = next;
+	}
+}
+
+/// How many lines a curve is split into so it's within [TOLERANCE], from how
+/// much it bends in pixels
+fn curve_steps(bend: f32) -> usize {
+	(bend / TOLERANCE).sqrt().ceil().clamp(1.0, 256.0) as usize
+}
+
+fn perpendicular(v: Vec2) -> Vec2 {
+	Vec2::new(-v.y, v.x)
+}
+
+fn unit(radians: f32) -> Vec2 {
+	Vec2::new(radians.cos(), radians.sin())
+}
+
+fn angle(v: Vec2) -> f32 {
+	v.y.atan2(v.x)
+}
+
+/// The same turn, but never more than half way around
+fn shortest(mut radians: f32) -> f32 {
+	while radians > PI {
+		radians -= PI * 2.0;
+	}
+	while radians <= -PI {
+		radians += PI * 2.0;
+	}
+	radians
+}
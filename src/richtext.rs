@@ -0,0 +1,244 @@
+use thiserror::Error;
+
+use crate::{text::SpanStyle, Color, FontId, TextAlign, TextLayout};
+
+/// Text with more than one style in it. Build it span by span, or parse it
+/// from markup with [RichText::parse].
+///
+/// ```ignore
+/// let text = RichText::new(font, 1.0)
+///     .color(Color::RED)
+///     .push("HP")
+///     .reset()
+///     .push(" 10");
+/// ```
+#[derive(Clone, Debug)]
+pub struct RichText {
+	base: SpanStyle,
+	style: SpanStyle,
+	spans: Vec<(String, SpanStyle)>,
+	max_width: Option<f32>,
+	align: TextAlign,
+	line_spacing: f32,
+}
+
+impl RichText {
+	pub fn new(font: FontId, scale: f32) -> Self {
+		let base = SpanStyle::new(font, scale);
+
+		Self {
+			base,
+			style: base,
+			spans: vec![],
+			max_width: None,
+			align: TextAlign::Left,
+			line_spacing: 1.0,
+		}
+	}
+
+	/// Parse markup like `[color=red]HP[/color] 10`. The tags are:
+	///
+	/// - `[color=red]` or `[color=1.0, 0.5, 0.0]`, the same as [Color]'s
+	///   `FromStr` with a few names on top.
+	/// - `[scale=0.5]`
+	/// - `[font=name]`, where `name` is looked up in `fonts`
+	/// - `[u]` for underline and `[s]` for strikethrough
+	///
+	/// Every tag is closed with `[/tag]`, which goes back to the style from
+	/// before it was opened. Write `[[` for a literal `[`.
+	pub fn parse(
+		markup: &str,
+		font: FontId,
+		scale: f32,
+		fonts: &[(&str, FontId)],
+	) -> Result<Self, RichTextError> {
+		let mut rich = Self::new(font, scale);
+		// The tag that was opened and the style before it
+		let mut stack: Vec<(&str, SpanStyle)> = vec![];
+		let mut rest = markup;
+
+		while let Some(open) = rest.find('[') {
+			rich = rich.push(&rest[..open]);
+			rest = &rest[open + 1..];
+
+			if let Some(after) = rest.strip_prefix('[') {
+				rich = rich.push("[");
+				rest = after;
+				continue;
+			}
+
+			let close = rest
+				.find(']')
+				.ok_or_else(|| RichTextError::UnclosedBracket(rest.into()))?;
+			let tag = &rest[..close];
+			rest = &rest[close + 1..];
+
+			if let Some(name) = tag.strip_prefix('/') {
+				match stack.pop() {
+					Some((open, style)) if open == name.trim() => rich.style = style,
+					_ => return Err(RichTextError::UnexpectedClose(name.into())),
+				}
+
+				continue;
+			}
+
+			let (name, value) = match tag.split_once('=') {
+				Some((name, value)) => (name.trim(), Some(value.trim())),
+				None => (tag.trim(), None),
+			};
+			stack.push((name, rich.style));
+
+			rich = match (name, value) {
+				("color", Some(value)) => rich.color(parse_color(value)?),
+				("scale", Some(value)) => rich.scale(
+					value
+						.parse()
+						.map_err(|_| RichTextError::InvalidValue(tag.into()))?,
+				),
+				("font", Some(value)) => {
+					let (_, id) = fonts
+						.iter()
+						.find(|(name, _)| *name == value)
+						.ok_or_else(|| RichTextError::UnknownFont(value.into()))?;
+
+					rich.font(*id)
+				}
+				("u", None) => rich.underline(true),
+				("s", None) => rich.strikethrough(true),
+				_ => return Err(RichTextError::UnknownTag(tag.into())),
+			};
+		}
+
+		if let Some((open, _)) = stack.pop() {
+			return Err(RichTextError::UnclosedTag(open.into()));
+		}
+
+		Ok(rich.push(rest))
+	}
+
+	/// Add text in the current style
+	pub fn push<S: AsRef<str>>(mut self, text: S) -> Self {
+		let text = text.as_ref();
+		if text.is_empty() {
+			return self;
+		}
+
+		match self.spans.last_mut() {
+			Some((last, style)) if *style == self.style => last.push_str(text),
+			_ => self.spans.push((text.into(), self.style)),
+		}
+
+		self
+	}
+
+	pub fn color(mut self, color: Color) -> Self {
+		self.style.color = Some(color);
+		self
+	}
+
+	pub fn scale(mut self, scale: f32) -> Self {
+		self.style.scale = scale;
+		self
+	}
+
+	pub fn font(mut self, font: FontId) -> Self {
+		self.style.font = font;
+		self
+	}
+
+	pub fn underline(mut self, underline: bool) -> Self {
+		self.style.underline = underline;
+		self
+	}
+
+	pub fn strikethrough(mut self, strikethrough: bool) -> Self {
+		self.style.strikethrough = strikethrough;
+		self
+	}
+
+	/// Go back to the font and scale we were made with, with no color or lines
+	pub fn reset(mut self) -> Self {
+		self.style = self.base;
+		self
+	}
+
+	/// Wrap lines that would be wider than `murs`
+	pub fn max_width(mut self, murs: f32) -> Self {
+		self.max_width = Some(murs);
+		self
+	}
+
+	pub fn align(mut self, align: TextAlign) -> Self {
+		self.align = align;
+		self
+	}
+
+	pub fn line_spacing(mut self, spacing: f32) -> Self {
+		self.line_spacing = spacing;
+		self
+	}
+
+	/// The text without any of the styling
+	pub fn plain(&self) -> String {
+		self.spans.iter().map(|(text, _)| text.as_str()).collect()
+	}
+
+	/// Turn this into a [TextLayout] so it can go through
+	/// [crate::Smitten::layout_text]
+	pub fn layout(&self) -> TextLayout {
+		let mut layout = TextLayout::from_spans(self.spans.clone()).line_spacing(self.line_spacing);
+		layout.align = self.align;
+		layout.max_width = self.max_width;
+
+		// Empty text still needs a style for its line height
+		if layout.spans.is_empty() {
+			layout.spans.push((String::new(), self.base));
+		}
+
+		layout
+	}
+}
+
+impl From<RichText> for TextLayout {
+	fn from(rich: RichText) -> Self {
+		rich.layout()
+	}
+}
+
+fn parse_color(value: &str) -> Result<Color, RichTextError> {
+	let named = match value.to_lowercase().as_str() {
+		"red" => Some(Color::RED),
+		"green" => Some(Color::GREEN),
+		"blue" => Some(Color::BLUE),
+		"yellow" => Some(Color::YELLOW),
+		"fuchsia" => Some(Color::FUCHSIA),
+		"aqua" => Some(Color::AQUA),
+		"black" => Some(Color::BLACK),
+		"white" => Some(Color::WHITE),
+		"transparent" => Some(Color::TRANSPARENT),
+		_ => None,
+	};
+
+	match named {
+		Some(color) => Ok(color),
+		None => Ok(value.parse()?),
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum RichTextError {
+	#[error("The tag [{0}] isn't one we know")]
+	UnknownTag(String),
+	#[error("The tag [{0}] has a value that could not be parsed")]
+	InvalidValue(String),
+	#[error(transparent)]
+	InvalidColor(#[from] crate::color::ColorParseError),
+	#[error("There's no font called {0}")]
+	UnknownFont(String),
+	#[error("[/{0}] doesn't close the last tag that was opened")]
+	UnexpectedClose(String),
+	#[error("The tag [{0}] was never closed")]
+	UnclosedTag(String),
+	#[error("A [ was never closed in: {0}")]
+	UnclosedBracket(String),
+}
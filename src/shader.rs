@@ -0,0 +1,208 @@
+use std::{
+	collections::HashMap,
+	fs, io,
+	path::{Path, PathBuf},
+	time::SystemTime,
+};
+
+use glow::{HasContext, Program};
+
+use crate::{
+	gl::{OpenGl, Texture, TEXTURE_VERTEX_SHADER},
+	Color, TextureId, Vec2,
+};
+
+/// A program made from user provided shaders.
+///
+/// The vertex shader gets the same inputs as our texture program: the model
+/// position at location 0, the texture coordinate at location 1, and the
+/// `WorldPosition`, `Scale`, `TextureOffset` and `TextureScale` uniforms.
+pub struct Shader {
+	pub program: Program,
+	vertex: ShaderSource,
+	fragment: ShaderSource,
+	uniforms: HashMap<String, Uniform>,
+}
+
+impl Shader {
+	pub fn from_files<V, F>(gl: &OpenGl, vertex: V, fragment: F) -> Self
+	where
+		V: AsRef<Path>,
+		F: AsRef<Path>,
+	{
+		Self::new(
+			gl,
+			ShaderSource::file(vertex.as_ref()),
+			ShaderSource::file(fragment.as_ref()),
+		)
+	}
+
+	/// Pairs the fragment shader with our own vertex shader
+	pub fn from_fragment_file<F: AsRef<Path>>(gl: &OpenGl, fragment: F) -> Self {
+		Self::new(
+			gl,
+			ShaderSource::Builtin(TEXTURE_VERTEX_SHADER),
+			ShaderSource::file(fragment.as_ref()),
+		)
+	}
+
+	fn new(gl: &OpenGl, vertex: ShaderSource, fragment: ShaderSource) -> Self {
+		let (vertex_source, fragment_source) = match (vertex.source(), fragment.source()) {
+			(Ok(vertex), Ok(fragment)) => (vertex, fragment),
+			(Err(e), _) | (_, Err(e)) => panic!("Failed to read shader: {e}"),
+		};
+
+		let program = match gl.create_user_program(&vertex_source, &fragment_source) {
+			Ok(program) => program,
+			Err(e) => panic!("{}", e),
+		};
+
+		Self {
+			program,
+			vertex,
+			fragment,
+			uniforms: HashMap::new(),
+		}
+	}
+
+	pub fn set_uniform(&mut self, name: String, value: Uniform) {
+		self.uniforms.insert(name, value);
+	}
+
+	/// Recompile the program if any of the source files changed on disk. If
+	/// the new sources can't be read or don't compile we complain and keep the
+	/// old program.
+	pub fn reload(&mut self, gl: &OpenGl) {
+		let vertex_changed = self.vertex.check_modified();
+		let fragment_changed = self.fragment.check_modified();
+
+		if !vertex_changed && !fragment_changed {
+			return;
+		}
+
+		// Editors that save by replacing the file can leave it missing for a
+		// moment. It'll change again once it's back.
+		let (vertex, fragment) = match (self.vertex.source(), self.fragment.source()) {
+			(Ok(vertex), Ok(fragment)) => (vertex, fragment),
+			(Err(e), _) | (_, Err(e)) => {
+				eprintln!("Failed to read shader: {e}");
+				return;
+			}
+		};
+
+		match gl.create_user_program(&vertex, &fragment) {
+			Ok(program) => {
+				gl.delete_user_program(self.program);
+				self.program = program;
+			}
+			Err(e) => eprintln!("Failed to reload shader: {e}"),
+		}
+	}
+
+	/// Bind the program and set all of the uniforms on it. Textures are given
+	/// texture units in the order they're found.
+	pub fn bind(&self, ogl: &OpenGl, textures: &HashMap<TextureId, Texture>) {
+		ogl.bind_user_program(self.program);
+
+		let gl = ogl.gl();
+		let mut unit = 0;
+
+		for (name, value) in &self.uniforms {
+			unsafe {
+				let location = gl.get_uniform_location(self.program, name);
+
+				match value {
+					Uniform::F32(f) => gl.uniform_1_f32(location.as_ref(), *f),
+					Uniform::Vec2(v) => gl.uniform_2_f32(location.as_ref(), v.x, v.y),
+					Uniform::Color(c) => gl.uniform_4_f32(location.as_ref(), c.r, c.g, c.b, c.a),
+					Uniform::Texture(tid) => {
+						gl.active_texture(glow::TEXTURE0 + unit);
+						textures.get(tid).unwrap().bind(ogl);
+						gl.uniform_1_i32(location.as_ref(), unit as i32);
+
+						unit += 1;
+					}
+				}
+			}
+		}
+
+		unsafe { gl.active_texture(glow::TEXTURE0) };
+	}
+}
+
+enum ShaderSource {
+	Builtin(&'static str),
+	File {
+		path: PathBuf,
+		modified: Option<SystemTime>,
+	},
+}
+
+impl ShaderSource {
+	fn file(path: &Path) -> Self {
+		Self::File {
+			path: path.to_owned(),
+			modified: Self::modified_time(path),
+		}
+	}
+
+	fn source(&self) -> io::Result<String> {
+		match self {
+			ShaderSource::Builtin(src) => Ok(src.to_string()),
+			ShaderSource::File { path, .. } => fs::read_to_string(path),
+		}
+	}
+
+	/// Whether or not the file changed since we last looked at it
+	fn check_modified(&mut self) -> bool {
+		match self {
+			ShaderSource::Builtin(_) => false,
+			ShaderSource::File { path, modified } => {
+				let current = Self::modified_time(path);
+
+				if current != *modified {
+					*modified = current;
+					true
+				} else {
+					false
+				}
+			}
+		}
+	}
+
+	fn modified_time(path: &Path) -> Option<SystemTime> {
+		fs::metadata(path).and_then(|meta| meta.modified()).ok()
+	}
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Uniform {
+	F32(f32),
+	Vec2(Vec2),
+	Color(Color),
+	Texture(TextureId),
+}
+
+impl From<f32> for Uniform {
+	fn from(f: f32) -> Self {
+		Uniform::F32(f)
+	}
+}
+
+impl From<Vec2> for Uniform {
+	fn from(v: Vec2) -> Self {
+		Uniform::Vec2(v)
+	}
+}
+
+impl From<Color> for Uniform {
+	fn from(c: Color) -> Self {
+		Uniform::Color(c)
+	}
+}
+
+impl From<TextureId> for Uniform {
+	fn from(tid: TextureId) -> Self {
+		Uniform::Texture(tid)
+	}
+}
@@ -1,134 +1,763 @@
-use std::{
-	collections::HashMap,
-	fs::File,
-	io::{BufWriter, Read},
-	path::Path,
-};
+use std::{cell::RefCell, collections::HashMap, fs::File, io::Read, path::Path, rc::Rc};
 
-use fontdue::Font;
-use image::ImageBuffer;
+use fontdue::{Font, FontSettings, Metrics};
+use thiserror::Error;
 
 use crate::{
-	gl::{OpenGl, Rectangle, Texture},
+	bitmapfont::BitmapFont,
+	gl::{Filter, OpenGl, Texture},
 	Vec2,
 };
 
+/// The size, in pixels, of text at a scale of 1.0 unless the font's
+/// [FontOptions] say otherwise. Bitmap glyphs are rasterized at this size and
+/// scaled from it when drawn.
+pub const RASTER_SIZE: f32 = 64.0;
+
+/// Width and height of an atlas page in pixels
+const PAGE_SIZE: usize = 1024;
+/// The biggest [FontOptions::size] a bitmap font can have. Glyphs can be
+/// wider than they are tall so this leaves them room on a page.
+pub const MAX_RASTER_SIZE: f32 = PAGE_SIZE as f32 / 2.0;
+/// How many atlas pages we'll make before we start evicting them
+const MAX_PAGES: usize = 4;
+/// Empty space around every glyph in the atlas so that filtering doesn't pull
+/// in the neighbours.
+const GLYPH_PADDING: usize = 1;
+
+/// The size glyphs of distance field fonts are stored at. They stay sharp
+/// when drawn much bigger than this.
+pub const SDF_SIZE: f32 = 48.0;
+/// How far, in pixels at [SDF_SIZE], the distance field reaches out from the
+/// edge of a glyph. Outlines and glows can't be wider than this.
+pub const SDF_SPREAD: usize = 6;
+/// Distance fields are computed from a raster this many times bigger than
+/// [SDF_SIZE] so the edges are found precisely.
+const SDF_OVERSAMPLE: usize = 4;
+
+/// The face of glyphs that come from a font's [BitmapFont]
+const BITMAP_FACE: usize = usize::MAX;
+
+/// How a font's glyphs are stored in its atlas
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum FontKind {
+	/// Coverage rasterized at the font's size. Gets blurry scaled up.
+	#[default]
+	Bitmap,
+	/// The distance to the glyph's edge, which stays crisp at any scale and
+	/// can draw outlines and glows.
+	DistanceField,
+}
+
+/// How a font is loaded and rasterized
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontOptions {
+	pub kind: FontKind,
+	/// How many pixels tall text is at a scale of 1.0. Bitmap fonts are
+	/// rasterized at this size, so pixel fonts want their native size here.
+	pub size: f32,
+	/// Characters to put in the atlas as soon as the font is made rather than
+	/// the first time they're drawn.
+	pub preload: String,
+	pub filter: Filter,
+	/// Which font to use from a collection, like a .ttc file
+	pub collection_index: u32,
+	/// Put every glyph on a whole pixel so pixel fonts stay crisp
+	pub pixel_snap: bool,
+}
+
+impl Default for FontOptions {
+	fn default() -> Self {
+		Self {
+			kind: FontKind::Bitmap,
+			size: RASTER_SIZE,
+			preload: String::new(),
+			filter: Filter::Linear,
+			collection_index: 0,
+			pixel_snap: false,
+		}
+	}
+}
+
+impl FontOptions {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn distance_field() -> Self {
+		Self::new().kind(FontKind::DistanceField)
+	}
+
+	/// For pixel fonts that are `size` pixels tall. They're drawn with nearest
+	/// filtering and snapped to the pixel grid, so scales should be whole
+	/// numbers.
+	pub fn pixel(size: f32) -> Self {
+		Self::new()
+			.size(size)
+			.filter(Filter::Nearest)
+			.pixel_snap(true)
+	}
+
+	pub fn kind(mut self, kind: FontKind) -> Self {
+		self.kind = kind;
+		self
+	}
+
+	pub fn size(mut self, size: f32) -> Self {
+		self.size = size;
+		self
+	}
+
+	pub fn preload<S: Into<String>>(mut self, chars: S) -> Self {
+		self.preload = chars.into();
+		self
+	}
+
+	pub fn filter(mut self, filter: Filter) -> Self {
+		self.filter = filter;
+		self
+	}
+
+	pub fn collection_index(mut self, index: u32) -> Self {
+		self.collection_index = index;
+		self
+	}
+
+	pub fn pixel_snap(mut self, snap: bool) -> Self {
+		self.pixel_snap = snap;
+		self
+	}
+
+	/// Whether glyphs at this size fit in the atlas
+	fn check(&self) -> Result<(), FontError> {
+		let fits = self.size > 0.0 && self.size <= MAX_RASTER_SIZE;
+
+		match self.kind {
+			FontKind::Bitmap if !fits => Err(FontError::InvalidSize(self.size)),
+			_ => Ok(()),
+		}
+	}
+}
+
 pub struct SmittenFont {
-	pub font: Font,
-	pub packed: PackedFont,
+	/// The primary face first and then the fallbacks in the order they're
+	/// looked through.
+	faces: Vec<Face>,
+	/// Looked in before the faces, if it's there
+	pub(crate) bitmap: Option<BitmapFont>,
+	options: FontOptions,
+	cache: RefCell<GlyphCache>,
 }
 
 impl SmittenFont {
-	pub fn from_file<P: AsRef<Path>>(gl: &OpenGl, path: P) -> Self {
-		let font = parse_font_file(path);
-		let packed = layout_texture(&font, gl);
+	/// A font made of multiple files. The first is the primary and the rest are
+	/// searched, in order, for glyphs the ones before don't have.
+	pub fn family_with<P: AsRef<Path>>(
+		paths: &[P],
+		options: FontOptions,
+	) -> Result<Self, FontError> {
+		if paths.is_empty() {
+			return Err(FontError::EmptyFamily);
+		}
+		options.check()?;
+
+		let mut font = Self::empty(options);
 
-		Self { font, packed }
+		for path in paths {
+			font.add_bytes(read_font_file(path)?)?;
+		}
+
+		Ok(font)
+	}
+
+	/// A font from a file that's already in memory, like one from `include_bytes!`
+	pub fn from_bytes<B: Into<Vec<u8>>>(data: B, options: FontOptions) -> Result<Self, FontError> {
+		options.check()?;
+		let mut font = Self::empty(options);
+		font.add_bytes(data.into())?;
+		Ok(font)
+	}
+
+	/// A font that was drawn ahead of time. Text is as big as the bitmap at a
+	/// scale of 1.0, and the bitmap's pages are drawn with `filter`.
+	pub fn from_bitmap(bitmap: BitmapFont, filter: Filter) -> Self {
+		let options = FontOptions::new()
+			.size(bitmap.size)
+			.filter(filter)
+			.pixel_snap(filter == Filter::Nearest);
+
+		let mut font = Self::empty(options);
+		font.bitmap = Some(bitmap);
+		font
+	}
+
+	fn empty(options: FontOptions) -> Self {
+		let raster_size = match options.kind {
+			FontKind::Bitmap => options.size,
+			FontKind::DistanceField => SDF_SIZE,
+		};
+
+		Self {
+			faces: vec![],
+			bitmap: None,
+			cache: RefCell::new(GlyphCache::new(options.kind, raster_size, options.filter)),
+			options,
+		}
+	}
+
+	fn add_bytes(&mut self, data: Vec<u8>) -> Result<(), FontError> {
+		let index = self.options.collection_index;
+		self.add_face(parse_font(&data, index)?, data.into(), index);
+		Ok(())
+	}
+
+	pub fn kind(&self) -> FontKind {
+		self.options.kind
+	}
+
+	/// The size glyphs are put in the atlas at
+	pub fn raster_size(&self) -> f32 {
+		self.cache.borrow().raster_size
+	}
+
+	/// How many pixels tall text is at `scale`
+	pub fn pixel_size(&self, scale: f32) -> f32 {
+		self.options.size * scale
+	}
+
+	pub fn pixel_snap(&self) -> bool {
+		self.options.pixel_snap
+	}
+
+	/// Rasterize the characters in the options' preload set
+	pub fn preload(&self, gl: &OpenGl) {
+		for ch in self.options.preload.chars() {
+			self.glyph(gl, self.glyph_index(ch));
+		}
+	}
+
+	/// Every face, primary first
+	pub fn faces(&self) -> &[Face] {
+		&self.faces
+	}
+
+	/// Add the faces to the end of our fallbacks
+	pub fn add_fallback(&mut self, faces: Vec<Face>) {
+		for face in faces {
+			self.add_face(face.font, face.data, face.index);
+		}
+	}
+
+	fn add_face(&mut self, font: Font, data: Rc<[u8]>, index: u32) {
+		let scale = match self.faces.first() {
+			None => 1.0,
+			Some(primary) => match (line_height(&primary.font), line_height(&font)) {
+				(Some(primary), Some(fallback)) if fallback > 0.0 => primary / fallback,
+				_ => 1.0,
+			},
+		};
+
+		self.faces.push(Face {
+			font,
+			data,
+			index,
+			scale,
+		});
+	}
+
+	/// The glyph used to draw `ch`, looking through the fallbacks if we have
+	/// to. If no face has it we use the replacement character, or if nothing
+	/// has that either, the primary face's missing glyph (glyph zero). Bitmap
+	/// fonts without faces use a question mark, or their first glyph.
+	pub fn glyph_index(&self, ch: char) -> Glyph {
+		self.lookup(ch)
+			.or_else(|| self.lookup(char::REPLACEMENT_CHARACTER))
+			.unwrap_or_else(|| match (&self.bitmap, self.faces.is_empty()) {
+				(Some(_), true) => self.lookup('?').unwrap_or(Glyph {
+					face: BITMAP_FACE,
+					index: 0,
+				}),
+				_ => Glyph { face: 0, index: 0 },
+			})
+	}
+
+	fn lookup(&self, ch: char) -> Option<Glyph> {
+		if let Some(index) = self.bitmap.as_ref().and_then(|b| b.lookup(ch)) {
+			return Some(Glyph {
+				face: BITMAP_FACE,
+				index,
+			});
+		}
+
+		self.faces
+			.iter()
+			.enumerate()
+			.find_map(|(face, f)| match f.font.lookup_glyph_index(ch) {
+				0 => None,
+				index => Some(Glyph { face, index }),
+			})
+	}
+
+	pub fn metrics(&self, glyph: Glyph, size: f32) -> Metrics {
+		if let (BITMAP_FACE, Some(bitmap)) = (glyph.face, &self.bitmap) {
+			return bitmap.metrics(glyph.index, size);
+		}
+
+		let face = &self.faces[glyph.face];
+		face.font.metrics_indexed(glyph.index, size * face.scale)
+	}
+
+	/// Distance between baselines of consecutive lines
+	pub fn line_height(&self, size: f32) -> f32 {
+		if let Some(bitmap) = &self.bitmap {
+			return bitmap.line_height * size / bitmap.size;
+		}
+
+		self.faces
+			.first()
+			.and_then(|face| face.font.horizontal_line_metrics(size))
+			.map(|metrics| metrics.new_line_size)
+			.unwrap_or(size * 1.2)
+	}
+
+	/// How much closer, or further, `right` should be to `left` when it
+	/// follows it. Glyphs from different faces don't kern.
+	pub fn kern(&self, left: Glyph, right: Glyph, size: f32) -> f32 {
+		if left.face != right.face {
+			return 0.0;
+		}
+
+		if let (BITMAP_FACE, Some(bitmap)) = (left.face, &self.bitmap) {
+			return bitmap.kern(left.index, right.index, size);
+		}
+
+		let face = &self.faces[left.face];
+		face.font
+			.horizontal_kern_indexed(left.index, right.index, size * face.scale)
+			.unwrap_or(0.0)
+	}
+
+	/// Where the glyph is in the atlas, rasterizing it if it isn't already
+	pub fn glyph(&self, gl: &OpenGl, glyph: Glyph) -> CachedGlyph {
+		if let (BITMAP_FACE, Some(bitmap)) = (glyph.face, &self.bitmap) {
+			return bitmap.glyph(glyph.index);
+		}
+
+		self.cache
+			.borrow_mut()
+			.get(gl, &self.faces[glyph.face], glyph)
+	}
+
+	/// Bind the texture `glyph` is on, where `page` is from [SmittenFont::glyph]
+	pub fn bind_page(&self, gl: &OpenGl, glyph: Glyph, page: usize) {
+		if let (BITMAP_FACE, Some(bitmap)) = (glyph.face, &self.bitmap) {
+			return bitmap.bind_page(gl, page);
+		}
+
+		if let Some(page) = self.cache.borrow().pages.get(page) {
+			unsafe { page.texture.bind(gl) }
+		}
 	}
 }
 
-fn layout_texture(font: &Font, gl: &OpenGl) -> PackedFont {
-	let size = 64.0f32;
+/// One font file in a [SmittenFont]
+#[derive(Clone)]
+pub struct Face {
+	pub font: Font,
+	/// The file itself, kept around for shaping
+	pub data: Rc<[u8]>,
+	/// Which font in the file this is, if it's a collection
+	pub index: u32,
+	/// Fallback faces are scaled so their lines are as tall as the primary
+	/// face's, otherwise mixed lines look uneven.
+	pub scale: f32,
+}
+
+/// A glyph from a specific face of a [SmittenFont]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Glyph {
+	pub face: usize,
+	pub index: u16,
+}
+
+fn line_height(font: &Font) -> Option<f32> {
+	font.horizontal_line_metrics(1.0)
+		.map(|metrics| metrics.ascent - metrics.descent)
+}
+
+/// Glyphs rasterized on demand and packed into atlas pages. When we've run
+/// out of room the page that was used the longest ago is emptied out.
+struct GlyphCache {
+	kind: FontKind,
+	/// The size glyphs are rasterized at, before the face's scale
+	raster_size: f32,
+	filter: Filter,
+	pages: Vec<AtlasPage>,
+	glyphs: HashMap<Glyph, CachedGlyph>,
+	/// Increases on every lookup so we know which page was used least recently
+	tick: u64,
+}
+
+impl GlyphCache {
+	fn new(kind: FontKind, raster_size: f32, filter: Filter) -> Self {
+		Self {
+			kind,
+			raster_size,
+			filter,
+			pages: vec![],
+			glyphs: HashMap::new(),
+			tick: 0,
+		}
+	}
+
+	fn get(&mut self, gl: &OpenGl, face: &Face, glyph: Glyph) -> CachedGlyph {
+		self.tick += 1;
+
+		let cached = match self.glyphs.get(&glyph) {
+			Some(cached) => *cached,
+			None => {
+				let cached = match self.kind {
+					FontKind::Bitmap => self.rasterize(gl, face, glyph.index),
+					FontKind::DistanceField => self.rasterize_distance(gl, face, glyph.index),
+				};
+				self.glyphs.insert(glyph, cached);
+				cached
+			}
+		};
+
+		// Glyphs too big for a page aren't on one
+		if let Some(page) = self.pages.get_mut(cached.page) {
+			page.last_used = self.tick;
+		}
+
+		cached
+	}
 
-	let width = (size * 17.0).ceil() as usize;
-	let height = (size * 7.0).ceil() as usize;
+	fn rasterize(&mut self, gl: &OpenGl, face: &Face, index: u16) -> CachedGlyph {
+		let (metrics, raster) = face
+			.font
+			.rasterize_indexed(index, self.raster_size * face.scale);
 
-	let mut characters = HashMap::new();
+		let offset = Vec2::new(metrics.xmin as f32, metrics.ymin as f32);
+		self.insert(gl, metrics.width, metrics.height, &raster, offset)
+	}
+
+	/// Make a distance field of the glyph from a big raster of it
+	fn rasterize_distance(&mut self, gl: &OpenGl, face: &Face, index: u16) -> CachedGlyph {
+		let size = self.raster_size * SDF_OVERSAMPLE as f32 * face.scale;
+		let (metrics, raster) = face.font.rasterize_indexed(index, size);
+
+		// Room for the field to spread out into, rounded so the big raster
+		// shrinks evenly into the atlas.
+		let pad = SDF_SPREAD * SDF_OVERSAMPLE;
+		let round = |n: usize| n.div_ceil(SDF_OVERSAMPLE) * SDF_OVERSAMPLE;
+		let big_width = round(metrics.width + pad * 2);
+		let big_height = round(metrics.height + pad * 2);
+		// Extra rows go on top so the bottom stays where ymin says it is
+		let top = big_height - metrics.height - pad;
+
+		let mut inside = vec![false; big_width * big_height];
+		for y in 0..metrics.height {
+			for x in 0..metrics.width {
+				inside[(y + top) * big_width + x + pad] = raster[y * metrics.width + x] >= 128;
+			}
+		}
 
-	let mut image = vec![0; width * height * 4];
+		let outside: Vec<bool> = inside.iter().map(|i| !i).collect();
+		let to_inside = distance_transform(&inside, big_width, big_height);
+		let to_outside = distance_transform(&outside, big_width, big_height);
 
-	for ch_index in 32..128 {
-		let ch = char::from_u32(ch_index).unwrap();
-		let (metrics, raster) = font.rasterize(ch, size);
+		let width = big_width / SDF_OVERSAMPLE;
+		let height = big_height / SDF_OVERSAMPLE;
+		let mut field = vec![0; width * height];
 
-		let (x_over, y_down) = {
-			let idx = ch_index as usize - 32;
-			let x = idx % 16;
-			let y = idx / 16;
+		for y in 0..height {
+			for x in 0..width {
+				// Average the signed distance of the block of big pixels
+				let mut sum = 0.0;
+				for by in y * SDF_OVERSAMPLE..(y + 1) * SDF_OVERSAMPLE {
+					for bx in x * SDF_OVERSAMPLE..(x + 1) * SDF_OVERSAMPLE {
+						let idx = by * big_width + bx;
+						// Half a pixel so the edge is between pixels, not on them
+						sum += if inside[idx] {
+							-(to_outside[idx].sqrt() - 0.5)
+						} else {
+							to_inside[idx].sqrt() - 0.5
+						};
+					}
+				}
 
-			(x * size as usize, y * size as usize)
+				let distance = sum / (SDF_OVERSAMPLE * SDF_OVERSAMPLE * SDF_OVERSAMPLE) as f32;
+				let value = 0.5 - distance / (SDF_SPREAD * 2) as f32;
+				field[y * width + x] = (value.clamp(0.0, 1.0) * 255.0) as u8;
+			}
+		}
+
+		let offset = Vec2::new(
+			(metrics.xmin - pad as i32) as f32 / SDF_OVERSAMPLE as f32,
+			(metrics.ymin - pad as i32) as f32 / SDF_OVERSAMPLE as f32,
+		);
+		self.insert(gl, width, height, &field, offset)
+	}
+
+	/// Put a `width` by `height` single channel image in the atlas
+	fn insert(
+		&mut self,
+		gl: &OpenGl,
+		width: usize,
+		height: usize,
+		alpha: &[u8],
+		offset: Vec2,
+	) -> CachedGlyph {
+		let padded_width = width + GLYPH_PADDING * 2;
+		let padded_height = height + GLYPH_PADDING * 2;
+		let (page, x, y) = match self.allocate(gl, padded_width, padded_height) {
+			Some(place) => place,
+			// Too big for a page, so it's left out rather than drawn wrong. It
+			// has no size so it doesn't matter what page it says it's on.
+			None => {
+				return CachedGlyph {
+					page: 0,
+					texture_position: Vec2::ZERO,
+					texture_dimensions: Vec2::ZERO,
+					offset,
+					size: Vec2::ZERO,
+				}
+			}
 		};
 
-		for x in 0..metrics.width {
-			for y in 0..metrics.height {
-				let raster_idx = y * metrics.width + x;
-				let image_idx = ((y_down + y) * width + (x_over + x)) * 4;
+		let mut image = vec![0; padded_width * padded_height * 4];
+		for y in 0..height {
+			for x in 0..width {
+				let alpha_idx = y * width + x;
+				let image_idx = ((y + GLYPH_PADDING) * padded_width + (x + GLYPH_PADDING)) * 4;
 
 				image[image_idx] = 255;
 				image[image_idx + 1] = 255;
 				image[image_idx + 2] = 255;
-				image[image_idx + 3] = raster[raster_idx];
+				image[image_idx + 3] = alpha[alpha_idx];
 			}
 		}
 
-		let size = Vec2::new(metrics.width as f32, metrics.height as f32);
-		let texture_position =
-			Vec2::new(x_over as f32 / width as f32, y_down as f32 / height as f32);
-		let texture_dimensions = Vec2::new(
-			metrics.width as f32 / width as f32,
-			metrics.height as f32 / height as f32,
-		);
+		self.pages[page]
+			.texture
+			.update(gl, x, y, padded_width, padded_height, &image);
 
-		println!("{ch} {texture_position} {texture_dimensions}");
+		CachedGlyph {
+			page,
+			texture_position: Vec2::new(
+				(x + GLYPH_PADDING) as f32 / PAGE_SIZE as f32,
+				(y + GLYPH_PADDING) as f32 / PAGE_SIZE as f32,
+			),
+			texture_dimensions: Vec2::new(
+				width as f32 / PAGE_SIZE as f32,
+				height as f32 / PAGE_SIZE as f32,
+			),
+			offset,
+			size: Vec2::new(width as f32, height as f32),
+		}
+	}
 
-		let rect = Rectangle::with_texture_coordinates(
-			gl.gl(),
-			/*size.normalize(),*/ Vec2::new(2.0, 2.0),
-			texture_position,
-			texture_dimensions,
-		);
+	/// Find room for a `width` by `height` glyph, returning the page and where
+	/// on that page it goes. `None` if it's bigger than a page.
+	fn allocate(
+		&mut self,
+		gl: &OpenGl,
+		width: usize,
+		height: usize,
+	) -> Option<(usize, usize, usize)> {
+		if width > PAGE_SIZE || height > PAGE_SIZE {
+			return None;
+		}
 
-		characters.insert(
-			ch,
-			PackedCharacter {
-				size,
-				texture_position,
-				texture_dimensions,
-				rect,
-			},
-		);
-	}
+		for (idx, page) in self.pages.iter_mut().enumerate() {
+			if let Some((x, y)) = page.allocate(width, height) {
+				return Some((idx, x, y));
+			}
+		}
 
-	let texture = Texture::rgba8(gl, width, height, &image);
+		let idx = if self.pages.len() < MAX_PAGES {
+			self.pages.push(AtlasPage::new(gl, self.filter));
+			self.pages.len() - 1
+		} else {
+			let (idx, _) = self
+				.pages
+				.iter()
+				.enumerate()
+				.min_by_key(|(_, page)| page.last_used)
+				.unwrap();
 
-	/*let file = File::create("font.png").unwrap();
-	let ref mut w = BufWriter::new(file);
-	let mut encoder = png::Encoder::new(w, width as u32, height as u32);
-	encoder.set_color(png::ColorType::Rgba);
-	encoder.set_depth(png::BitDepth::Eight);
-	encoder
-		.write_header()
-		.unwrap()
-		.write_image_data(&image)
-		.unwrap();*/
+			self.glyphs.retain(|_, glyph| glyph.page != idx);
+			self.pages[idx].reset();
+			idx
+		};
 
-	PackedFont {
-		texture,
-		characters,
+		self.pages[idx]
+			.allocate(width, height)
+			.map(|(x, y)| (idx, x, y))
 	}
 }
 
-pub struct PackedFont {
-	pub texture: Texture,
-	pub characters: HashMap<char, PackedCharacter>,
+/// A texture glyphs are packed into. Glyphs are placed left to right on
+/// shelves as tall as the tallest glyph on them.
+struct AtlasPage {
+	texture: Texture,
+	shelf_x: usize,
+	shelf_y: usize,
+	shelf_height: usize,
+	last_used: u64,
 }
 
-pub struct PackedCharacter {
+impl AtlasPage {
+	fn new(gl: &OpenGl, filter: Filter) -> Self {
+		Self {
+			texture: Texture::atlas(gl, PAGE_SIZE, PAGE_SIZE, filter),
+			shelf_x: 0,
+			shelf_y: 0,
+			shelf_height: 0,
+			last_used: 0,
+		}
+	}
+
+	fn allocate(&mut self, width: usize, height: usize) -> Option<(usize, usize)> {
+		if self.shelf_x + width > PAGE_SIZE {
+			self.shelf_x = 0;
+			self.shelf_y += self.shelf_height;
+			self.shelf_height = 0;
+		}
+
+		if width > PAGE_SIZE || self.shelf_y + height > PAGE_SIZE {
+			return None;
+		}
+
+		let position = (self.shelf_x, self.shelf_y);
+		self.shelf_x += width;
+		self.shelf_height = self.shelf_height.max(height);
+
+		Some(position)
+	}
+
+	/// Forget everything on the page. The old pixels stay in the texture but
+	/// will be drawn over as new glyphs come in.
+	fn reset(&mut self) {
+		self.shelf_x = 0;
+		self.shelf_y = 0;
+		self.shelf_height = 0;
+	}
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct CachedGlyph {
+	pub page: usize,
+	pub texture_position: Vec2,
+	pub texture_dimensions: Vec2,
+	/// Where the bottom left of the image is from the pen, in pixels at the
+	/// font's [SmittenFont::raster_size]. Y goes up.
+	pub offset: Vec2,
+	/// Size of the image in pixels at the font's raster size
 	pub size: Vec2,
-	texture_position: Vec2,
-	texture_dimensions: Vec2,
+}
+
+/// The squared distance from every pixel to the nearest one that's set in
+/// `mask`. This is Felzenszwalb and Huttenlocher's transform, one dimension
+/// at a time: first every column, then every row.
+fn distance_transform(mask: &[bool], width: usize, height: usize) -> Vec<f32> {
+	let mut grid: Vec<f32> = mask
+		.iter()
+		.map(|set| if *set { 0.0 } else { INFINITY })
+		.collect();
+
+	let longest = width.max(height);
+	let mut f = vec![0.0; longest];
+	let mut d = vec![0.0; longest];
+	let mut v = vec![0; longest];
+	let mut z = vec![0.0; longest + 1];
 
-	pub rect: Rectangle,
+	for x in 0..width {
+		for y in 0..height {
+			f[y] = grid[y * width + x];
+		}
+
+		distance_transform_1d(&f[..height], &mut d, &mut v, &mut z);
+
+		for y in 0..height {
+			grid[y * width + x] = d[y];
+		}
+	}
+
+	for y in 0..height {
+		f[..width].copy_from_slice(&grid[y * width..(y + 1) * width]);
+		distance_transform_1d(&f[..width], &mut d, &mut v, &mut z);
+		grid[y * width..(y + 1) * width].copy_from_slice(&d[..width]);
+	}
+
+	grid
 }
 
-pub fn parse_font_file<P: AsRef<Path>>(path: P) -> Font {
-	let mut file = File::open(path.as_ref()).unwrap();
+/// Stands in for infinity without turning the maths into NaNs
+const INFINITY: f32 = 1e20;
+
+/// The lower envelope of the parabolas rooted at every `f`
+fn distance_transform_1d(f: &[f32], d: &mut [f32], v: &mut [usize], z: &mut [f32]) {
+	let parabola = |q: usize| f[q] + (q * q) as f32;
+	let intersect = |q: usize, r: usize| (parabola(q) - parabola(r)) / (2 * q - 2 * r) as f32;
+
+	let mut k = 0;
+	v[0] = 0;
+	z[0] = -INFINITY;
+	z[1] = INFINITY;
+
+	for q in 1..f.len() {
+		let mut s = intersect(q, v[k]);
+		while s <= z[k] {
+			k -= 1;
+			s = intersect(q, v[k]);
+		}
+
+		k += 1;
+		v[k] = q;
+		z[k] = s;
+		z[k + 1] = INFINITY;
+	}
+
+	k = 0;
+	for (q, distance) in d.iter_mut().enumerate().take(f.len()) {
+		while z[k + 1] < q as f32 {
+			k += 1;
+		}
+
+		let dq = q as f32 - v[k] as f32;
+		*distance = dq * dq + f[v[k]];
+	}
+}
+
+pub fn read_font_file<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<u8>> {
+	let mut file = File::open(path.as_ref())?;
 	let mut buffer = vec![];
-	file.read_to_end(&mut buffer).unwrap();
+	file.read_to_end(&mut buffer)?;
+
+	Ok(buffer)
+}
+
+/// Parse a font file. `collection_index` picks the font out of a `.ttc`
+/// collection and should be zero for everything else.
+pub fn parse_font(data: &[u8], collection_index: u32) -> Result<Font, FontError> {
+	let settings = FontSettings {
+		collection_index,
+		..Default::default()
+	};
 
-	parse_font(&buffer)
+	Font::from_bytes(data, settings).map_err(FontError::Parse)
 }
 
-pub fn parse_font(data: &[u8]) -> Font {
-	Font::from_bytes(data, Default::default()).unwrap()
+#[derive(Debug, Error)]
+pub enum FontError {
+	#[error("Could not read the font: {0}")]
+	Io(#[from] std::io::Error),
+	#[error("Could not parse the font: {0}")]
+	Parse(&'static str),
+	#[error("A font family needs at least one font")]
+	EmptyFamily,
+	#[error("Bitmap fonts can't be {0} pixels, they have to be more than 0 and at most {MAX_RASTER_SIZE}")]
+	InvalidSize(f32),
 }
@@ -0,0 +1,788 @@
+use std::collections::HashMap;
+
+use crate::{
+	smittenfont::{Glyph, SmittenFont},
+	Color, FontId, Vec2,
+};
+
+/// Text to be laid out over one or more lines. Lines break on newlines and,
+/// if there's a maximum width, between words.
+#[derive(Clone, Debug)]
+pub struct TextLayout {
+	pub(crate) spans: Vec<(String, SpanStyle)>,
+	pub(crate) max_width: Option<f32>,
+	pub(crate) align: TextAlign,
+	pub(crate) line_spacing: f32,
+}
+
+impl TextLayout {
+	pub fn new<S: Into<String>>(font: FontId, text: S, scale: f32) -> Self {
+		Self::from_spans(vec![(text.into(), SpanStyle::new(font, scale))])
+	}
+
+	/// Text made of differently styled spans, see [crate::RichText]
+	pub fn from_spans(spans: Vec<(String, SpanStyle)>) -> Self {
+		Self {
+			spans,
+			max_width: None,
+			align: TextAlign::Left,
+			line_spacing: 1.0,
+		}
+	}
+
+	/// Wrap lines that would be wider than `murs`
+	pub fn max_width(mut self, murs: f32) -> Self {
+		self.max_width = Some(murs);
+		self
+	}
+
+	pub fn align(mut self, align: TextAlign) -> Self {
+		self.align = align;
+		self
+	}
+
+	/// Multiplies the distance between lines. `1.0` is the font's own line height.
+	pub fn line_spacing(mut self, spacing: f32) -> Self {
+		self.line_spacing = spacing;
+		self
+	}
+}
+
+/// How a span of text looks
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpanStyle {
+	pub font: FontId,
+	pub scale: f32,
+	/// The color given when drawing is used if this is `None`
+	pub color: Option<Color>,
+	pub underline: bool,
+	pub strikethrough: bool,
+}
+
+impl SpanStyle {
+	pub fn new(font: FontId, scale: f32) -> Self {
+		Self {
+			font,
+			scale,
+			color: None,
+			underline: false,
+			strikethrough: false,
+		}
+	}
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum TextAlign {
+	#[default]
+	Left,
+	Center,
+	Right,
+	/// Stretch the spaces so every line but the last of a paragraph fills the
+	/// maximum width
+	Justify,
+}
+
+/// Outlines, glows and shadows drawn along with text. Sizes are in murs.
+///
+/// Glows need a distance field font, see [crate::Smitten::make_sdf_font], and
+/// can't reach further than the field does. Outlines and shadows work with
+/// every font, but bitmap fonts get their outline by drawing the text around
+/// itself so it's best kept thin.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TextEffects {
+	pub outline: Option<(Color, f32)>,
+	pub glow: Option<(Color, f32)>,
+	pub shadow: Option<TextShadow>,
+}
+
+impl TextEffects {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn outline(mut self, color: Color, width: f32) -> Self {
+		self.outline = Some((color, width));
+		self
+	}
+
+	pub fn glow(mut self, color: Color, radius: f32) -> Self {
+		self.glow = Some((color, radius));
+		self
+	}
+
+	/// A copy of the text behind it, moved by `offset` and blurred by `softness`
+	pub fn shadow<V: Into<Vec2>>(mut self, color: Color, offset: V, softness: f32) -> Self {
+		self.shadow = Some(TextShadow {
+			color,
+			offset: offset.into(),
+			softness,
+		});
+		self
+	}
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct TextShadow {
+	pub color: Color,
+	pub offset: Vec2,
+	/// Only distance field fonts can blur their shadow
+	pub softness: f32,
+}
+
+/// How a single glyph is drawn. Given to the callback of
+/// [crate::Smitten::write_with] so glyphs can be moved, colored and scaled one
+/// by one.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GlyphDraw {
+	/// Moves the glyph, in murs
+	pub offset: Vec2,
+	pub color: Color,
+	/// Scales the glyph around its center
+	pub scale: f32,
+}
+
+/// Text that's been laid out and is ready to be drawn with
+/// [crate::Smitten::draw_text].
+pub struct LaidOutText {
+	/// All of the spans' text together. Clusters index into this.
+	pub(crate) text: String,
+	pub(crate) spans: Vec<SpanStyle>,
+	pub(crate) glyphs: Vec<LaidGlyph>,
+	pub(crate) decorations: Vec<Decoration>,
+	/// Y of every line's baseline in pixels from the top
+	pub(crate) baselines: Vec<f32>,
+	/// Width and height in pixels
+	pub(crate) dimensions: Vec2,
+	pub(crate) mur_size: u32,
+}
+
+impl LaidOutText {
+	/// Width and height of the text in murs
+	pub fn bounds(&self) -> Vec2 {
+		self.dimensions / self.mur_size
+	}
+
+	pub fn line_count(&self) -> usize {
+		self.baselines.len()
+	}
+
+	/// The character that starts the cluster of every glyph, in drawing order
+	pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+		self.glyphs.iter().map(|g| {
+			self.text[g.cluster..]
+				.chars()
+				.next()
+				.unwrap_or(char::REPLACEMENT_CHARACTER)
+		})
+	}
+
+	/// Measurements of the text in murs
+	pub fn metrics(&self) -> TextMetrics {
+		let mur = |f: f32| f / self.mur_size as f32;
+
+		let first_baseline = self.baselines.first().copied().unwrap_or(0.0);
+		let last_baseline = self.baselines.last().copied().unwrap_or(0.0);
+
+		TextMetrics {
+			width: mur(self.dimensions.x),
+			height: mur(self.dimensions.y),
+			ascent: mur(first_baseline),
+			descent: mur(last_baseline - self.dimensions.y),
+			baseline: mur(self.dimensions.y - last_baseline),
+			glyphs: self
+				.glyphs
+				.iter()
+				.map(|g| GlyphMetrics {
+					cluster: g.cluster,
+					line: g.line,
+					x: mur(g.position.x),
+					advance: mur(g.advance),
+				})
+				.collect(),
+		}
+	}
+}
+
+/// The size of some text, in murs. See [crate::Smitten::measure_text]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextMetrics {
+	pub width: f32,
+	pub height: f32,
+	/// How far above the first line's baseline the text goes
+	pub ascent: f32,
+	/// How far below the last line's baseline the text goes. This is negative
+	/// or zero, like fontdue's `ymin`.
+	pub descent: f32,
+	/// Distance from the bottom of the text up to the last line's baseline
+	pub baseline: f32,
+	/// Every glyph in the order it's drawn
+	pub glyphs: Vec<GlyphMetrics>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GlyphMetrics {
+	/// Byte offset into the text of the cluster this glyph is part of
+	pub cluster: usize,
+	pub line: usize,
+	/// Where the glyph starts, from the left of the text
+	pub x: f32,
+	/// How far the glyph moves the next one along
+	pub advance: f32,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct LaidGlyph {
+	pub glyph: Glyph,
+	pub span: usize,
+	pub cluster: usize,
+	pub line: usize,
+	/// Where the pen is for this glyph, on the baseline, in pixels from the top
+	/// left of the text. Y goes down.
+	pub position: Vec2,
+	pub advance: f32,
+}
+
+/// An underline or strikethrough
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Decoration {
+	pub span: usize,
+	/// The left end of the line, in pixels from the top left of the text
+	pub start: Vec2,
+	pub width: f32,
+	pub thickness: f32,
+}
+
+/// Lay out text in pixels. See [TextLayout]
+pub(crate) fn layout(
+	fonts: &HashMap<FontId, SmittenFont>,
+	layout: &TextLayout,
+	mur_size: u32,
+) -> LaidOutText {
+	let font = |span: usize| fonts.get(&layout.spans[span].1.font).unwrap();
+	let size = |span: usize| font(span).pixel_size(layout.spans[span].1.scale);
+	let max_width = layout.max_width.map(|murs| murs * mur_size as f32);
+
+	let mut lines: Vec<Line> = vec![];
+	for paragraph in paragraphs(layout) {
+		let mut glyphs = vec![];
+
+		for run in &paragraph {
+			let text = &layout.spans[run.span].0[run.range.clone()];
+
+			for shaped in shape(font(run.span), text, size(run.span)) {
+				glyphs.push(LineGlyph {
+					shaped,
+					span: run.span,
+					cluster: run.offset + shaped.cluster,
+					space: text[shaped.cluster..]
+						.chars()
+						.next()
+						.map(char::is_whitespace)
+						.unwrap_or(false),
+				});
+			}
+		}
+
+		let mut broken = break_lines(&glyphs, max_width);
+
+		// Empty lines still need to be as tall as what they would've held
+		if let (Some(first), Some(run)) = (broken.first_mut(), paragraph.first()) {
+			first.empty_span = run.span;
+		}
+
+		if let Some(last) = broken.last_mut() {
+			last.ends_paragraph = true;
+		}
+
+		lines.extend(broken);
+	}
+
+	let text: String = layout.spans.iter().map(|(text, _)| text.as_str()).collect();
+	let widest = lines.iter().fold(0.0f32, |w, line| w.max(line.width));
+	let width = match (layout.align, max_width) {
+		(TextAlign::Justify, Some(max)) => widest.max(max),
+		_ => widest,
+	};
+
+	let mut glyphs = vec![];
+	let mut decorations = vec![];
+	let mut baselines = vec![];
+	let mut top = 0.0f32;
+	let mut bottom = 0.0f32;
+	let mut baseline = 0.0;
+
+	for (idx, line) in lines.iter().enumerate() {
+		// Mixed sizes share a baseline, and the line is as tall as the tallest
+		// span on it.
+		if idx > 0 {
+			let line_height = if line.glyphs.is_empty() {
+				font(line.empty_span).line_height(size(line.empty_span))
+			} else {
+				line.glyphs
+					.iter()
+					.map(|g| font(g.span).line_height(size(g.span)))
+					.fold(0.0f32, f32::max)
+			};
+
+			baseline += line_height * layout.line_spacing;
+		}
+
+		bottom = bottom.max(baseline);
+		baselines.push(baseline);
+
+		let slack = width - line.width;
+		// Spaces the line ends on are past its width, so they don't stretch
+		let inked = line.glyphs.len() - line.glyphs.iter().rev().take_while(|g| g.space).count();
+		let spaces = line.glyphs[..inked].iter().filter(|g| g.space).count();
+		let (mut x, space_stretch) = match layout.align {
+			TextAlign::Left => (0.0, 0.0),
+			TextAlign::Center => (slack / 2.0, 0.0),
+			TextAlign::Right => (slack, 0.0),
+			TextAlign::Justify if !line.ends_paragraph && spaces > 0 => {
+				(0.0, slack / spaces as f32)
+			}
+			TextAlign::Justify => (0.0, 0.0),
+		};
+
+		let first_glyph = glyphs.len();
+		for (glyph_idx, lg) in line.glyphs.iter().enumerate() {
+			let shaped = lg.shaped;
+			let metrics = font(lg.span).metrics(shaped.glyph, size(lg.span));
+
+			// Track the ink so the bounds are tight around what's drawn
+			let ascent = metrics.height as f32 + metrics.ymin as f32 + shaped.y_offset;
+			let descent = metrics.ymin as f32 + shaped.y_offset;
+			top = top.min(baseline - ascent);
+			bottom = bottom.max(baseline - descent);
+
+			let stretch = lg.space && glyph_idx < inked;
+			let advance = shaped.x_advance + if stretch { space_stretch } else { 0.0 };
+			glyphs.push(LaidGlyph {
+				glyph: shaped.glyph,
+				span: lg.span,
+				cluster: lg.cluster,
+				line: idx,
+				position: Vec2::new(x + shaped.x_offset, baseline - shaped.y_offset),
+				advance,
+			});
+
+			x += advance;
+		}
+
+		for decoration in decorate(&text, &layout.spans, &glyphs[first_glyph..], baseline, size) {
+			bottom = bottom.max(decoration.start.y + decoration.thickness / 2.0);
+			decorations.push(decoration);
+		}
+	}
+
+	// Move everything so the top of the ink is at zero
+	for glyph in &mut glyphs {
+		glyph.position.y -= top;
+	}
+	for decoration in &mut decorations {
+		decoration.start.y -= top;
+	}
+	baselines.iter_mut().for_each(|b| *b -= top);
+
+	LaidOutText {
+		text,
+		spans: layout.spans.iter().map(|(_, style)| *style).collect(),
+		glyphs,
+		decorations,
+		baselines,
+		dimensions: Vec2::new(width, bottom - top),
+		mur_size,
+	}
+}
+
+/// Underlines and strikethroughs for the glyphs of one line. Neighbouring
+/// glyphs of the same span get one continuous line, without trailing spaces.
+/// `text` is all of the spans together, which is what clusters index into.
+fn decorate<F: Fn(usize) -> f32>(
+	text: &str,
+	spans: &[(String, SpanStyle)],
+	glyphs: &[LaidGlyph],
+	baseline: f32,
+	size: F,
+) -> Vec<Decoration> {
+	let mut decorations = vec![];
+	let mut idx = 0;
+
+	while idx < glyphs.len() {
+		let span = glyphs[idx].span;
+		let run_end = glyphs[idx..]
+			.iter()
+			.position(|g| g.span != span)
+			.map(|len| idx + len)
+			.unwrap_or(glyphs.len());
+		let run = &glyphs[idx..run_end];
+		idx = run_end;
+
+		let style = spans[span].1;
+		if !style.underline && !style.strikethrough {
+			continue;
+		}
+
+		let inked = run
+			.iter()
+			.rposition(|g| {
+				!text[g.cluster..]
+					.chars()
+					.next()
+					.map(char::is_whitespace)
+					.unwrap_or(false)
+			})
+			.map(|last| &run[..=last])
+			.unwrap_or(&[]);
+
+		let (first, last) = match (inked.first(), inked.last()) {
+			(Some(first), Some(last)) => (first, last),
+			_ => continue,
+		};
+
+		let size = size(span);
+		let thickness = (size / 16.0).max(1.0);
+		let left = first.position.x;
+		let width = last.position.x + last.advance - left;
+
+		if style.underline {
+			decorations.push(Decoration {
+				span,
+				start: Vec2::new(left, baseline + size * 0.1),
+				width,
+				thickness,
+			});
+		}
+
+		if style.strikethrough {
+			decorations.push(Decoration {
+				span,
+				start: Vec2::new(left, baseline - size * 0.3),
+				width,
+				thickness,
+			});
+		}
+	}
+
+	decorations
+}
+
+/// A piece of one span that's inside one paragraph
+struct Run {
+	span: usize,
+	/// Byte range of the span's text
+	range: std::ops::Range<usize>,
+	/// Byte offset of this run from the start of all the text
+	offset: usize,
+}
+
+/// Split the spans on newlines. Runs that follow each other in a paragraph
+/// are laid out on the same lines.
+fn paragraphs(layout: &TextLayout) -> Vec<Vec<Run>> {
+	let mut paragraphs = vec![vec![]];
+	let mut offset = 0;
+
+	for (span, (text, _)) in layout.spans.iter().enumerate() {
+		let mut start = 0;
+
+		for (idx, piece) in text.split('\n').enumerate() {
+			if idx > 0 {
+				paragraphs.push(vec![]);
+			}
+
+			paragraphs.last_mut().unwrap().push(Run {
+				span,
+				range: start..start + piece.len(),
+				offset: offset + start,
+			});
+
+			start += piece.len() + 1;
+		}
+
+		offset += text.len();
+	}
+
+	paragraphs
+}
+
+struct Line {
+	glyphs: Vec<LineGlyph>,
+	/// Width without the trailing whitespace
+	width: f32,
+	ends_paragraph: bool,
+	/// The span whose line height we use if there are no glyphs
+	empty_span: usize,
+}
+
+#[derive(Copy, Clone)]
+struct LineGlyph {
+	shaped: ShapedGlyph,
+	span: usize,
+	/// Cluster relative to the start of the whole text, not the span
+	cluster: usize,
+	space: bool,
+}
+
+/// Break a paragraph into lines no wider than `max_width`, preferring to break
+/// after whitespace. Words longer than a line are split wherever they have to be.
+fn break_lines(glyphs: &[LineGlyph], max_width: Option<f32>) -> Vec<Line> {
+	let mut lines = vec![];
+	let mut start = 0;
+
+	while start < glyphs.len() {
+		let mut end = glyphs.len();
+		let mut x = 0.0;
+		// Index just past the last whitespace we've seen on this line
+		let mut last_break = None;
+
+		for (idx, glyph) in glyphs.iter().enumerate().skip(start) {
+			if glyph.space {
+				last_break = Some(idx + 1);
+			} else if let Some(max) = max_width {
+				let overflowing = x + glyph.shaped.x_advance > max;
+
+				// Always keep at least one cluster on a line
+				if overflowing && glyphs[idx].cluster != glyphs[start].cluster {
+					end = match last_break {
+						Some(brk) => brk,
+						None => cluster_start(glyphs, idx),
+					};
+					break;
+				}
+			}
+
+			x += glyph.shaped.x_advance;
+		}
+
+		lines.push(make_line(&glyphs[start..end]));
+		start = end;
+	}
+
+	if lines.is_empty() {
+		lines.push(make_line(&[]));
+	}
+
+	lines
+}
+
+/// The first glyph of the cluster that `idx` is in, so we don't break between
+/// a character and its marks.
+fn cluster_start(glyphs: &[LineGlyph], idx: usize) -> usize {
+	let cluster = glyphs[idx].cluster;
+	let mut start = idx;
+
+	while start > 0 && glyphs[start - 1].cluster == cluster {
+		start -= 1;
+	}
+
+	start
+}
+
+fn make_line(glyphs: &[LineGlyph]) -> Line {
+	let trailing = glyphs.iter().rev().take_while(|g| g.space).count();
+	let width = glyphs[..glyphs.len() - trailing]
+		.iter()
+		.map(|g| g.shaped.x_advance)
+		.sum();
+
+	Line {
+		glyphs: glyphs.to_vec(),
+		width,
+		ends_paragraph: false,
+		empty_span: glyphs.first().map(|g| g.span).unwrap_or(0),
+	}
+}
+
+/// A glyph placed by [shape]. Everything is in pixels at the size the text
+/// was shaped at.
+#[derive(Copy, Clone, Debug)]
+pub struct ShapedGlyph {
+	pub glyph: Glyph,
+	/// Byte offset into the text of the start of the cluster this glyph is
+	/// part of. A base character and the marks on it share a cluster.
+	pub cluster: usize,
+	/// How far to move the pen after this glyph
+	pub x_advance: f32,
+	/// Where to draw this glyph relative to the pen
+	pub x_offset: f32,
+	pub y_offset: f32,
+}
+
+/// Turn text into glyphs and their positions.
+///
+/// Without the `shaping` feature every character becomes one glyph. Pairs are
+/// kerned and combining marks are centered over the character before them,
+/// which covers most Latin, Greek and Cyrillic text. With it, text is
+/// shaped by rustybuzz which handles ligatures and complex scripts.
+pub fn shape(font: &SmittenFont, text: &str, size: f32) -> Vec<ShapedGlyph> {
+	#[cfg(feature = "shaping")]
+	if let Some(shaped) = shape_rustybuzz(font, text, size) {
+		return shaped;
+	}
+
+	shape_simple(font, text, size)
+}
+
+fn shape_simple(font: &SmittenFont, text: &str, size: f32) -> Vec<ShapedGlyph> {
+	let mut shaped: Vec<ShapedGlyph> = vec![];
+	let mut cluster = 0;
+	let mut base_advance = 0.0;
+
+	for (idx, ch) in text.char_indices() {
+		if is_invisible(ch) {
+			continue;
+		}
+
+		let glyph = font.glyph_index(ch);
+		let metrics = font.metrics(glyph, size);
+
+		if is_combining_mark(ch) && !shaped.is_empty() {
+			// Center the mark over the glyph it's on. The pen is already past
+			// that glyph so we have to go back half of it.
+			let mark_center = metrics.xmin as f32 + metrics.width as f32 / 2.0;
+
+			shaped.push(ShapedGlyph {
+				glyph,
+				cluster,
+				x_advance: 0.0,
+				x_offset: -(base_advance / 2.0) - mark_center,
+				y_offset: 0.0,
+			});
+
+			continue;
+		}
+
+		if let Some(previous) = shaped.iter_mut().rev().find(|sg| sg.x_advance != 0.0) {
+			previous.x_advance += font.kern(previous.glyph, glyph, size);
+		}
+
+		cluster = idx;
+		base_advance = metrics.advance_width;
+
+		shaped.push(ShapedGlyph {
+			glyph,
+			cluster,
+			x_advance: metrics.advance_width,
+			x_offset: 0.0,
+			y_offset: 0.0,
+		});
+	}
+
+	shaped
+}
+
+#[cfg(feature = "shaping")]
+fn shape_rustybuzz(font: &SmittenFont, text: &str, size: f32) -> Option<Vec<ShapedGlyph>> {
+	// Bitmap fonts have nothing to shape with
+	if font.bitmap.is_some() {
+		return None;
+	}
+
+	let primary = font.faces().first()?;
+	let face = rustybuzz::Face::from_slice(&primary.data, primary.index)?;
+
+	let mut buffer = rustybuzz::UnicodeBuffer::new();
+	buffer.push_str(text);
+	buffer.guess_segment_properties();
+
+	let output = rustybuzz::shape(&face, &[], buffer);
+	let scale = (size * primary.scale) / face.units_per_em() as f32;
+
+	let mut shaped = vec![];
+	for (info, position) in output.glyph_infos().iter().zip(output.glyph_positions()) {
+		let cluster = info.cluster as usize;
+
+		if info.glyph_id != 0 {
+			shaped.push(ShapedGlyph {
+				glyph: Glyph {
+					face: 0,
+					index: info.glyph_id as u16,
+				},
+				cluster,
+				x_advance: position.x_advance as f32 * scale,
+				x_offset: position.x_offset as f32 * scale,
+				y_offset: position.y_offset as f32 * scale,
+			});
+
+			continue;
+		}
+
+		// The primary face doesn't have it, so we look for the character in
+		// the fallbacks instead. They won't be shaped, but they'll be there.
+		let ch = match text[cluster..].chars().next() {
+			Some(ch) => ch,
+			None => continue,
+		};
+
+		let glyph = font.glyph_index(ch);
+		shaped.push(ShapedGlyph {
+			glyph,
+			cluster,
+			x_advance: font.metrics(glyph, size).advance_width,
+			x_offset: 0.0,
+			y_offset: 0.0,
+		});
+	}
+
+	Some(shaped)
+}
+
+/// Characters from the combining diacritical mark blocks. They're drawn on top
+/// of the character before them rather than after it.
+fn is_combining_mark(ch: char) -> bool {
+	matches!(ch,
+		'\u{0300}'..='\u{036F}'
+		| '\u{1AB0}'..='\u{1AFF}'
+		| '\u{1DC0}'..='\u{1DFF}'
+		| '\u{20D0}'..='\u{20FF}'
+		| '\u{FE20}'..='\u{FE2F}'
+	)
+}
+
+/// Characters that only change how the ones around them look. We can't do
+/// anything with them without shaping, and fonts usually don't have glyphs
+/// for them, so they're skipped.
+fn is_invisible(ch: char) -> bool {
+	matches!(ch,
+		'\u{200B}'..='\u{200F}'
+		| '\u{2060}'..='\u{2064}'
+		| '\u{FE00}'..='\u{FE0F}'
+		| '\u{FEFF}'
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn decorate_span_after_the_first() {
+		let mut underlined = SpanStyle::new(FontId(0), 1.0);
+		underlined.underline = true;
+		let spans = vec![
+			("Hello ".to_string(), SpanStyle::new(FontId(0), 1.0)),
+			("World ".to_string(), underlined),
+		];
+		let text: String = spans.iter().map(|(text, _)| text.as_str()).collect();
+
+		let glyphs: Vec<LaidGlyph> = text
+			.char_indices()
+			.map(|(cluster, _)| LaidGlyph {
+				glyph: Glyph { face: 0, index: 0 },
+				span: if cluster < 6 { 0 } else { 1 },
+				cluster,
+				line: 0,
+				position: Vec2::new(cluster as f32 * 10.0, 0.0),
+				advance: 10.0,
+			})
+			.collect();
+
+		let decorations = decorate(&text, &spans, &glyphs, 0.0, |_| 16.0);
+
+		assert_eq!(decorations.len(), 1);
+		assert_eq!(decorations[0].span, 1);
+		assert_eq!(decorations[0].start.x, 60.0);
+		// The trailing space isn't underlined
+		assert_eq!(decorations[0].width, 50.0);
+	}
+}